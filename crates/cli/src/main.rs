@@ -1,6 +1,9 @@
-use std::{fs, path::PathBuf};
+use std::{path::PathBuf, process::ExitCode};
 
 use clap::{Parser, Subcommand};
+use pandalang_eval::{Evaluator, Value};
+use pandalang_parser::module::ModuleId;
+use pandalang_types::Globals;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -14,21 +17,73 @@ enum Commands {
     /// Starts the repl
     Repl,
     /// Runs a program
-    Run { program: PathBuf },
+    Run {
+        program: PathBuf,
+        /// Don't make the definitions of the prelude available to the program
+        #[arg(long)]
+        no_prelude: bool,
+    },
 }
 
-fn main() -> Result<(), String> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{}", err.trim_end());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, String> {
     match cli.command {
-        Commands::Repl => pandalang_repl::run_repl(),
-        Commands::Run { program } => {
-            let src = fs::read_to_string(program).map_err(|err| err.to_string())?;
-            let ast = pandalang_parser::parse(&src).map_err(|err| err.to_string())?;
-            pandalang_types::check_prog_to_strings(ast.clone()).map_err(|err| err.to_string())?;
+        Commands::Repl => {
+            pandalang_repl::run_repl()?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Commands::Run {
+            program,
+            no_prelude,
+        } => {
+            let graph = pandalang_parser::module::load(&program).map_err(|err| err.render())?;
+            let globals = Globals {
+                prelude: !no_prelude,
+                ..Globals::default()
+            };
+            let checked =
+                pandalang_types::check_modules(graph.clone(), &globals).map_err(|errs| {
+                    graph.render_all(
+                        errs.iter()
+                            .map(|err| (err.module, err.error.to_diagnostic())),
+                    )
+                })?;
+            let warnings: Vec<_> = checked
+                .iter()
+                .enumerate()
+                .flat_map(|(module, checked)| {
+                    checked
+                        .warnings
+                        .iter()
+                        .map(move |warning| (ModuleId::Graph(module), warning.to_diagnostic()))
+                })
+                .collect();
+            if !warnings.is_empty() {
+                eprintln!("{}", graph.render_all(warnings));
+            }
             let mut stdout = std::io::stdout();
-            let value = pandalang_eval::run_program(ast, &mut stdout)?;
-            println!("{}", value);
-            Ok(())
+            let evaluator = if no_prelude {
+                Evaluator::without_prelude(&mut stdout)
+            } else {
+                Evaluator::new(&mut stdout)
+            };
+            let value = pandalang_eval::run_modules(evaluator, graph.clone())
+                .map_err(|err| graph.render_all([(err.module, err.error.to_diagnostic())]))?;
+            // An Int main is the exit code, which the OS truncates to a byte anyway
+            match value {
+                Value::Int(code) => Ok(ExitCode::from(code as u8)),
+                _ => Ok(ExitCode::SUCCESS),
+            }
         }
     }
 }
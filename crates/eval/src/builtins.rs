@@ -1,8 +1,8 @@
 use std::io::Write;
 
-use pandalang_parser::ast::expr::{Int, Str};
+use pandalang_parser::{prelude, span::Span};
 
-use crate::value::Value;
+use crate::{error::RuntimeError, value::Value};
 
 use super::BoundValue;
 
@@ -15,30 +15,228 @@ impl<'a> Builtins<'a> {
         Self { stdout }
     }
 
-    pub fn eval(&mut self, builtin_name: String, arg: BoundValue) -> Result<BoundValue, String> {
-        match builtin_name.as_str() {
-            "str_of_int" => self.str_of_int(arg),
-            "println" => self.println_(arg),
-            _ => Err("Builtin not found".to_string()),
+    pub fn eval(
+        &mut self,
+        builtin_name: String,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<BoundValue, RuntimeError> {
+        let result = match (builtin_name.as_str(), args.as_slice()) {
+            ("str_of_int", [x]) => self.str_of_int(x),
+            ("float_of_int", [x]) => self.float_of_int(x),
+            ("int_of_float", [x]) => self.int_of_float(x),
+            ("str_of_float", [x]) => self.str_of_float(x),
+            ("println", [x]) => self.println_(x),
+            ("length", [xs]) => self.length(xs),
+            ("append", [xs, ys]) => self.append(xs, ys),
+            ("str_concat", [s, t]) => self.str_concat(s, t),
+            ("str_length", [s]) => self.str_length(s),
+            ("substring", [start, len, s]) => self.substring(start, len, s),
+            ("index_of", [needle, s]) => self.index_of(needle, s),
+            ("char_at", [i, s]) => self.char_at(i, s),
+            ("split", [sep, s]) => self.split(sep, s),
+            ("join", [sep, xs]) => self.join(sep, xs),
+            ("trim", [s]) => self.map_str(s, |s| s.trim().to_string()),
+            ("to_upper", [s]) => self.map_str(s, str::to_uppercase),
+            ("to_lower", [s]) => self.map_str(s, str::to_lowercase),
+            ("int_of_str", [s]) => self.int_of_str(s),
+            _ => Err("Builtin not found".into()),
+        };
+        result.map_err(|message| match message {
+            BuiltinError::Io(message) => RuntimeError::Io { message },
+            BuiltinError::Failed(message) => RuntimeError::Builtin {
+                name: builtin_name,
+                message,
+                span,
+            },
+        })
+    }
+
+    fn str_of_int(&self, x: &Value) -> Result<BoundValue, BuiltinError> {
+        match x {
+            Value::Int(n) => Ok(BoundValue::Value(Value::Str(n.to_string()))),
+            _ => Err("Not an Int".into()),
         }
     }
 
-    fn str_of_int(&self, x: BoundValue) -> Result<BoundValue, String> {
+    fn float_of_int(&self, x: &Value) -> Result<BoundValue, BuiltinError> {
         match x {
-            BoundValue::Value(Value::Int(Int { n })) => {
-                Ok(BoundValue::Value(Value::Str(Str { s: n.to_string() })))
-            }
-            _ => Err("Not an Int".to_string()),
+            Value::Int(n) => Ok(BoundValue::Value(Value::Float(*n as f64))),
+            _ => Err("Not an Int".into()),
+        }
+    }
+
+    /// Rounds towards zero, saturating at the smallest and largest Ints
+    fn int_of_float(&self, x: &Value) -> Result<BoundValue, BuiltinError> {
+        match x {
+            Value::Float(x) if x.is_finite() => Ok(BoundValue::Value(Value::Int(*x as i64))),
+            Value::Float(_) => Err("Not a finite Float".into()),
+            _ => Err("Not a Float".into()),
         }
     }
 
-    fn println_(&mut self, x: BoundValue) -> Result<BoundValue, String> {
+    fn str_of_float(&self, x: &Value) -> Result<BoundValue, BuiltinError> {
         match x {
-            BoundValue::Value(Value::Str(Str { s })) => {
-                writeln!(self.stdout, "{}", s).map_err(|err| err.to_string())?;
+            Value::Float(_) => Ok(BoundValue::Value(Value::Str(x.to_string()))),
+            _ => Err("Not a Float".into()),
+        }
+    }
+
+    fn println_(&mut self, x: &Value) -> Result<BoundValue, BuiltinError> {
+        match x {
+            Value::Str(s) => {
+                writeln!(self.stdout, "{}", s).map_err(|err| BuiltinError::Io(err.to_string()))?;
                 Ok(BoundValue::Value(Value::Unit))
             }
-            _ => Err("Not a Str".to_string()),
+            _ => Err("Not a Str".into()),
+        }
+    }
+
+    fn length(&self, xs: &Value) -> Result<BoundValue, BuiltinError> {
+        match xs {
+            Value::List(elems) => Ok(BoundValue::Value(Value::Int(elems.len() as i64))),
+            _ => Err("Not a List".into()),
+        }
+    }
+
+    fn append(&self, xs: &Value, ys: &Value) -> Result<BoundValue, BuiltinError> {
+        match (xs, ys) {
+            (Value::List(xs), Value::List(ys)) => Ok(BoundValue::Value(Value::List(
+                xs.iter().chain(ys).cloned().collect(),
+            ))),
+            _ => Err("Not a List".into()),
+        }
+    }
+
+    fn str_concat(&self, s: &Value, t: &Value) -> Result<BoundValue, BuiltinError> {
+        match (s, t) {
+            (Value::Str(s), Value::Str(t)) => {
+                Ok(BoundValue::Value(Value::Str(format!("{}{}", s, t))))
+            }
+            _ => Err("Not a Str".into()),
+        }
+    }
+
+    // Indices and lengths count chars, via `chars().count()` and `chars().nth`
+
+    fn str_length(&self, s: &Value) -> Result<BoundValue, BuiltinError> {
+        match s {
+            Value::Str(s) => Ok(BoundValue::Value(Value::Int(s.chars().count() as i64))),
+            _ => Err("Not a Str".into()),
+        }
+    }
+
+    /// The `len` chars of `s` from index `start`, which must all be in `s`
+    fn substring(&self, start: &Value, len: &Value, s: &Value) -> Result<BoundValue, BuiltinError> {
+        match (start, len, s) {
+            (Value::Int(start), Value::Int(len), Value::Str(s)) => {
+                let (Ok(start), Ok(len)) = (usize::try_from(*start), usize::try_from(*len)) else {
+                    return Err("Negative index".into());
+                };
+                if start.saturating_add(len) > s.chars().count() {
+                    return Err("Out of bounds".into());
+                }
+                let sub = s.chars().skip(start).take(len).collect();
+                Ok(BoundValue::Value(Value::Str(sub)))
+            }
+            _ => Err("Expected two Ints and a Str".into()),
+        }
+    }
+
+    /// The index of the first occurrence of `needle` in `s`, if there is one
+    fn index_of(&self, needle: &Value, s: &Value) -> Result<BoundValue, BuiltinError> {
+        match (needle, s) {
+            (Value::Str(needle), Value::Str(s)) => {
+                let index = s
+                    .find(needle.as_str())
+                    .map(|i| Value::Int(s[..i].chars().count() as i64));
+                Ok(BoundValue::Value(option(index)))
+            }
+            _ => Err("Not a Str".into()),
         }
     }
+
+    /// The char at index `i` of `s` as a Str, if there is one
+    fn char_at(&self, i: &Value, s: &Value) -> Result<BoundValue, BuiltinError> {
+        match (i, s) {
+            (Value::Int(i), Value::Str(s)) => {
+                let c = usize::try_from(*i)
+                    .ok()
+                    .and_then(|i| s.chars().nth(i))
+                    .map(|c| Value::Str(c.to_string()));
+                Ok(BoundValue::Value(option(c)))
+            }
+            _ => Err("Expected an Int and a Str".into()),
+        }
+    }
+
+    /// The parts of `s` between occurrences of `sep`
+    fn split(&self, sep: &Value, s: &Value) -> Result<BoundValue, BuiltinError> {
+        match (sep, s) {
+            (Value::Str(sep), _) if sep.is_empty() => Err("Empty separator".into()),
+            (Value::Str(sep), Value::Str(s)) => {
+                let parts = s
+                    .split(sep.as_str())
+                    .map(|part| Value::Str(part.to_string()))
+                    .collect();
+                Ok(BoundValue::Value(Value::List(parts)))
+            }
+            _ => Err("Not a Str".into()),
+        }
+    }
+
+    /// The strings of `xs` with `sep` between each of them
+    fn join(&self, sep: &Value, xs: &Value) -> Result<BoundValue, BuiltinError> {
+        let (Value::Str(sep), Value::List(xs)) = (sep, xs) else {
+            return Err("Expected a Str and a List".into());
+        };
+        let parts = xs
+            .iter()
+            .map(|x| match x {
+                Value::Str(s) => Ok(s.as_str()),
+                _ => Err(BuiltinError::from("Not a List of Strs")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BoundValue::Value(Value::Str(parts.join(sep))))
+    }
+
+    fn map_str(&self, s: &Value, f: impl Fn(&str) -> String) -> Result<BoundValue, BuiltinError> {
+        match s {
+            Value::Str(s) => Ok(BoundValue::Value(Value::Str(f(s)))),
+            _ => Err("Not a Str".into()),
+        }
+    }
+
+    /// Parses a base 10 Int, with an optional sign
+    fn int_of_str(&self, s: &Value) -> Result<BoundValue, BuiltinError> {
+        match s {
+            Value::Str(s) => Ok(BoundValue::Value(option(s.parse().ok().map(Value::Int)))),
+            _ => Err("Not a Str".into()),
+        }
+    }
+}
+
+/// A value of the prelude's `Option` type
+fn option(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::Data {
+            constructor: format!("{}.Some", prelude::MODULE),
+            fields: vec![value],
+        },
+        None => Value::Data {
+            constructor: format!("{}.None", prelude::MODULE),
+            fields: Vec::new(),
+        },
+    }
+}
+
+enum BuiltinError {
+    Failed(String),
+    Io(String),
+}
+
+impl From<&str> for BuiltinError {
+    fn from(message: &str) -> Self {
+        BuiltinError::Failed(message.to_string())
+    }
 }
@@ -0,0 +1,106 @@
+use pandalang_parser::{diagnostic::Diagnostic, span::Span};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub enum RuntimeError {
+    UnboundVariable {
+        name: String,
+        span: Span,
+    },
+    /// A value of the wrong kind was used, e.g. applying a non-function or adding non-Ints.
+    /// The type checker rules these out for checked programs.
+    BadOperand {
+        operation: String,
+        expected: String,
+        span: Span,
+    },
+    DivisionByZero {
+        span: Span,
+    },
+    /// The result of an arithmetic operator on Ints doesn't fit in an Int
+    IntegerOverflow {
+        span: Span,
+    },
+    Builtin {
+        name: String,
+        message: String,
+        span: Span,
+    },
+    /// None of the arms of a `match` matched the value
+    MatchFailure {
+        value: String,
+        span: Span,
+    },
+    MissingMain,
+    NotFullyEvaluated,
+    Io {
+        message: String,
+    },
+}
+
+impl RuntimeError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            RuntimeError::UnboundVariable { span, .. }
+            | RuntimeError::BadOperand { span, .. }
+            | RuntimeError::DivisionByZero { span }
+            | RuntimeError::IntegerOverflow { span }
+            | RuntimeError::Builtin { span, .. }
+            | RuntimeError::MatchFailure { span, .. } => Some(*span),
+            RuntimeError::MissingMain
+            | RuntimeError::NotFullyEvaluated
+            | RuntimeError::Io { .. } => None,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.to_string());
+        match self {
+            RuntimeError::UnboundVariable { span, .. } => {
+                diagnostic.with_label(*span, "not bound at runtime")
+            }
+            RuntimeError::BadOperand { expected, span, .. } => {
+                diagnostic.with_label(*span, format!("expected {}", expected))
+            }
+            RuntimeError::DivisionByZero { span } => {
+                diagnostic.with_label(*span, "the divisor evaluated to 0")
+            }
+            RuntimeError::IntegerOverflow { span } => diagnostic
+                .with_label(*span, "the result doesn't fit in an Int")
+                .with_note("Ints range from -9223372036854775808 to 9223372036854775807"),
+            RuntimeError::Builtin { span, .. } => {
+                diagnostic.with_label(*span, "in this call to a builtin")
+            }
+            RuntimeError::MatchFailure { value, span } => {
+                diagnostic.with_label(*span, format!("this evaluated to {}", value))
+            }
+            RuntimeError::MissingMain => {
+                diagnostic.with_note("programs start by evaluating a top-level `let main = ...`")
+            }
+            RuntimeError::NotFullyEvaluated | RuntimeError::Io { .. } => diagnostic,
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::UnboundVariable { name, .. } => write!(f, "{} is not bound", name),
+            RuntimeError::BadOperand {
+                operation,
+                expected,
+                ..
+            } => write!(f, "{} expects {}", operation, expected),
+            RuntimeError::DivisionByZero { .. } => write!(f, "Division by zero"),
+            RuntimeError::IntegerOverflow { .. } => write!(f, "Integer overflow"),
+            RuntimeError::Builtin { name, message, .. } => {
+                write!(f, "Builtin {} failed: {}", name, message)
+            }
+            RuntimeError::MatchFailure { .. } => write!(f, "No arm of the match matched"),
+            RuntimeError::MissingMain => write!(f, "Couldn't find main"),
+            RuntimeError::NotFullyEvaluated => {
+                write!(f, "Final value should be fully evaluated")
+            }
+            RuntimeError::Io { message } => write!(f, "I/O error: {}", message),
+        }
+    }
+}
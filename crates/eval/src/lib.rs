@@ -1,155 +1,632 @@
 mod builtins;
 pub mod env;
+mod error;
+pub mod registry;
 mod value;
 
-use std::io::Write;
+use std::{collections::HashMap, io::Write};
 
-use pandalang_parser::ast::expr::{App, BinOp, BinOpKind, Bool, Expr, Fun, If, Int, Let, Var};
+pub use error::RuntimeError;
+use pandalang_parser::ast::expr::{
+    Access, App, Arm, Ascribe, BinOp, BinOpKind, Cons, Constructor, Expr, Field, Fun, If, Let,
+    List, Match, Not, Record, Tuple, Update, Var,
+};
+use pandalang_parser::ast::pattern::{self, Pattern};
 use pandalang_parser::ast::stmt::Stmt;
 use pandalang_parser::ast::{stmt, Program};
+use pandalang_parser::module::{ModuleError, ModuleGraph, ModuleId};
+use pandalang_parser::prelude;
+use pandalang_parser::span::Span;
+pub use registry::{Registry, RegistryError};
 pub use value::Value;
 
 use self::builtins::Builtins;
 use self::env::{BoundValue, Env};
 
-pub fn run_program(program: Program, stdout: &mut dyn Write) -> Result<Value, String> {
-    let mut evaluator = Evaluator::new(stdout);
+pub fn run_program(program: Program, stdout: &mut dyn Write) -> Result<Value, RuntimeError> {
+    run_program_with(Evaluator::new(stdout), program)
+}
 
-    for stmt in program.stmts {
-        match stmt {
-            Stmt::Let(stmt::Let { name, value, rec }) => {
-                let value = evaluator.eval_let_value(name.clone(), *value, rec)?;
-                evaluator.env.push_binding(&name, value)
-            }
-            Stmt::Declare(stmt::Declare { name, .. }) => evaluator
-                .env
-                .push_binding(&name.clone(), BoundValue::Value(Value::Builtin(name))),
+/// Runs a program with an evaluator set up by the caller, e.g. with the builtins of a [Registry]
+pub fn run_program_with(mut evaluator: Evaluator, program: Program) -> Result<Value, RuntimeError> {
+    evaluator.run_module(program)?;
+    evaluator.main()
+}
+
+/// Runs a program split across files. Each module is run after the modules it imports, and the
+/// last one's `main` is the result. Every module starts with the bindings `evaluator` has, e.g.
+/// those of the prelude.
+pub fn run_modules(
+    mut evaluator: Evaluator,
+    graph: ModuleGraph,
+) -> Result<Value, ModuleError<RuntimeError>> {
+    let globals = evaluator.env.clone();
+    evaluator.modules = graph
+        .modules
+        .iter()
+        .map(|module| module.name.clone())
+        .collect();
+    let last = graph.modules.len().saturating_sub(1);
+
+    for (i, module) in graph.modules.into_iter().enumerate() {
+        evaluator.env = globals.clone();
+        evaluator.module = ModuleId::Graph(i);
+        let defined = evaluator
+            .run_module(module.program)
+            .map_err(|error| ModuleError {
+                module: evaluator.failed_in.unwrap_or(ModuleId::Graph(i)),
+                error,
+            })?;
+        if let Some(name) = module.name {
+            evaluator.exports.insert(name, defined);
         }
     }
 
-    let main = evaluator.env.lookup("main").ok_or("Couldn't find main")?;
-
-    check_fully_evaluated(main)
+    evaluator.main().map_err(|error| ModuleError {
+        module: evaluator.failed_in.unwrap_or(ModuleId::Graph(last)),
+        error,
+    })
 }
 
-pub fn eval(mut evaluator: Evaluator, expr: Expr) -> Result<Value, String> {
+pub fn eval(mut evaluator: Evaluator, expr: Expr) -> Result<Value, RuntimeError> {
     check_fully_evaluated(evaluator.eval(expr)?)
 }
 
-fn check_fully_evaluated(v: BoundValue) -> Result<Value, String> {
+fn check_fully_evaluated(v: BoundValue) -> Result<Value, RuntimeError> {
     match v {
         BoundValue::Value(v) => Ok(v),
-        BoundValue::Thunk(_) => Err("Final value should be fully evaluated".to_string()),
+        BoundValue::Thunk(_) => Err(RuntimeError::NotFullyEvaluated),
     }
 }
 
 pub struct Evaluator<'a> {
     env: Env,
     builtins: Builtins<'a>,
+    /// The builtins provided by the host, which take precedence over pandalang's own
+    registry: Registry,
+    /// The names of the modules being run, by index. Only imported modules have one, and it
+    /// qualifies the constructors they define.
+    modules: Vec<Option<String>>,
+    /// The module whose code is being evaluated
+    module: ModuleId,
+    /// The top-level bindings of each module run so far, by module name
+    exports: HashMap<String, Vec<(String, BoundValue)>>,
+    /// The module whose code failed, once evaluation has failed inside a function
+    failed_in: Option<ModuleId>,
 }
 
 impl<'a> Evaluator<'a> {
+    /// An evaluator in which the definitions of the prelude are in scope
     pub fn new(stdout: &'a mut dyn Write) -> Self {
+        let mut evaluator = Self::without_prelude(stdout);
+        evaluator.module = ModuleId::Prelude;
+        evaluator
+            .run_module(prelude::program())
+            .expect("the prelude runs");
+        evaluator.module = ModuleId::Graph(0);
+        evaluator
+    }
+
+    /// An evaluator in which only what the program defines itself is in scope
+    pub fn without_prelude(stdout: &'a mut dyn Write) -> Self {
         Self {
             env: Env::new(),
             builtins: Builtins::new(stdout),
+            registry: Registry::new(),
+            modules: vec![None],
+            module: ModuleId::Graph(0),
+            exports: HashMap::new(),
+            failed_in: None,
         }
     }
 
-    fn eval(&mut self, expr: Expr) -> Result<BoundValue, String> {
+    /// Makes the builtins of `registry` available, replacing any bindings of the same names
+    pub fn with_registry(mut self, registry: Registry) -> Self {
+        for (name, native) in registry.natives() {
+            let value = Value::builtin(name.clone(), native.arity);
+            self.env.push_binding(name, BoundValue::Value(value));
+        }
+        self.registry = registry;
+        self
+    }
+
+    /// Runs the top-level statements of a module, returning the bindings it defines
+    fn run_module(&mut self, program: Program) -> Result<Vec<(String, BoundValue)>, RuntimeError> {
+        let mut defined = Vec::new();
+        for stmt in program.stmts {
+            match stmt {
+                Stmt::Let(stmt::Let {
+                    name, value, rec, ..
+                }) => {
+                    let value = self.eval_let_value(name.clone(), *value, rec)?;
+                    self.define(&mut defined, name, value);
+                }
+                Stmt::LetPattern(stmt::LetPattern { pattern, value, .. }) => {
+                    let span = value.span();
+                    let value = self.eval(*value)?;
+                    let value = self.force(value)?;
+                    let mut bindings = Vec::new();
+                    let bound = BoundValue::Value(value.clone());
+                    if !match_pattern(&pattern, &bound, &self.env, &mut bindings) {
+                        return Err(RuntimeError::MatchFailure {
+                            value: value.to_string(),
+                            span,
+                        });
+                    }
+                    for (name, value) in bindings {
+                        self.define(&mut defined, name, value);
+                    }
+                }
+                Stmt::Declare(stmt::Declare { name, typ, .. }) => {
+                    // A builtin takes as many arguments as its declared type says
+                    let value = Value::builtin(name.clone(), typ.arity());
+                    self.define(&mut defined, name, BoundValue::Value(value));
+                }
+                Stmt::TypeDef(stmt::TypeDef { constructors, .. }) => {
+                    for stmt::ConstructorDef { name, fields, .. } in constructors {
+                        let constructor = qualify(self.module_name(), &name);
+                        let value = Value::constructor(constructor, fields.len());
+                        self.define(&mut defined, name, BoundValue::Value(value));
+                    }
+                }
+                Stmt::Import(stmt::Import { module, .. }) => {
+                    // The checker rejects imports that weren't resolved
+                    let imported = self.exports.get(&module).cloned().unwrap_or_default();
+                    for (name, value) in imported {
+                        self.env
+                            .push_binding(&format!("{}.{}", module, name), value);
+                    }
+                }
+            }
+        }
+        Ok(defined)
+    }
+
+    /// Binds a top-level definition of the module being run
+    fn define(&mut self, defined: &mut Vec<(String, BoundValue)>, name: String, value: BoundValue) {
+        self.env.push_binding(&name, value.clone());
+        defined.push((name, value));
+    }
+
+    fn main(&self) -> Result<Value, RuntimeError> {
+        let main = self.env.lookup("main").ok_or(RuntimeError::MissingMain)?;
+        check_fully_evaluated(main)
+    }
+
+    /// The name of the module whose code is being evaluated
+    fn module_name(&self) -> Option<&str> {
+        match self.module {
+            ModuleId::Prelude => Some(prelude::MODULE),
+            ModuleId::Graph(i) => self.modules[i].as_deref(),
+        }
+    }
+
+    fn eval(&mut self, expr: Expr) -> Result<BoundValue, RuntimeError> {
         match expr {
-            Expr::Int(n) => Ok(BoundValue::Value(Value::Int(n))),
-            Expr::Str(s) => Ok(BoundValue::Value(Value::Str(s))),
-            Expr::Unit => Ok(BoundValue::Value(Value::Unit)),
-            Expr::Bool(b) => Ok(BoundValue::Value(Value::Bool(b))),
-            Expr::Var(Var { name }) => Ok(self
-                .env
-                .lookup(&name)
-                .ok_or(format!("{} is not bound!", name))?),
-            Expr::BinOp(BinOp { left, right, kind }) => match kind {
-                BinOpKind::Add => self.eval_arith(*left, *right, std::ops::Add::add),
-                BinOpKind::Sub => self.eval_arith(*left, *right, std::ops::Sub::sub),
-                BinOpKind::Mul => self.eval_arith(*left, *right, std::ops::Mul::mul),
-                BinOpKind::Div => self.eval_arith(*left, *right, std::ops::Div::div),
-                BinOpKind::Rem => self.eval_arith(*left, *right, std::ops::Rem::rem),
-                BinOpKind::Eql => {
+            Expr::Int(n) => Ok(BoundValue::Value(Value::Int(n.n))),
+            Expr::Float(x) => Ok(BoundValue::Value(Value::Float(x.x))),
+            Expr::Str(s) => Ok(BoundValue::Value(Value::Str(s.s))),
+            Expr::Unit(_) => Ok(BoundValue::Value(Value::Unit)),
+            Expr::Bool(b) => Ok(BoundValue::Value(Value::Bool(b.b))),
+            Expr::Var(Var { name, span }) | Expr::Constructor(Constructor { name, span }) => {
+                match self.env.lookup(&name) {
+                    // A builtin that takes no arguments is run whenever it's used
+                    Some(BoundValue::Value(Value::Builtin {
+                        name,
+                        arity: 0,
+                        args,
+                    })) => self.eval_builtin(name, args, span),
+                    Some(value) => Ok(value),
+                    None => Err(RuntimeError::UnboundVariable { name, span }),
+                }
+            }
+            Expr::Tuple(Tuple { elems, .. }) => {
+                let elems = elems
+                    .into_iter()
+                    .map(|elem| {
+                        let elem = self.eval(elem)?;
+                        self.force(elem)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(BoundValue::Value(Value::Tuple(elems)))
+            }
+            Expr::List(List { elems, .. }) => {
+                let elems = elems
+                    .into_iter()
+                    .map(|elem| {
+                        let elem = self.eval(elem)?;
+                        self.force(elem)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(BoundValue::Value(Value::List(elems)))
+            }
+            Expr::Cons(Cons { head, tail, span }) => {
+                let head = self.eval(*head)?;
+                let head = self.force(head)?;
+                match self.eval(*tail)? {
+                    BoundValue::Value(Value::List(mut elems)) => {
+                        elems.insert(0, head);
+                        Ok(BoundValue::Value(Value::List(elems)))
+                    }
+                    _ => Err(RuntimeError::BadOperand {
+                        operation: "::".to_string(),
+                        expected: "a List".to_string(),
+                        span,
+                    }),
+                }
+            }
+            Expr::Record(Record { fields, .. }) => {
+                let fields = self.eval_fields(fields)?;
+                Ok(BoundValue::Value(Value::Record(
+                    fields.into_iter().collect(),
+                )))
+            }
+            Expr::Access(Access { expr, field, span }) => {
+                let value = match self.eval(*expr)? {
+                    BoundValue::Value(Value::Record(mut fields)) => fields.remove(&field),
+                    _ => None,
+                };
+                value
+                    .map(BoundValue::Value)
+                    .ok_or(RuntimeError::BadOperand {
+                        operation: format!("Accessing .{}", field),
+                        expected: format!("a record with a {} field", field),
+                        span,
+                    })
+            }
+            Expr::Update(Update { expr, fields, span }) => match self.eval(*expr)? {
+                BoundValue::Value(Value::Record(mut record)) => {
+                    record.extend(self.eval_fields(fields)?);
+                    Ok(BoundValue::Value(Value::Record(record)))
+                }
+                _ => Err(RuntimeError::BadOperand {
+                    operation: "Updating".to_string(),
+                    expected: "a record".to_string(),
+                    span,
+                }),
+            },
+            Expr::BinOp(BinOp {
+                left,
+                right,
+                kind,
+                span,
+            }) => match kind {
+                BinOpKind::Add
+                | BinOpKind::Sub
+                | BinOpKind::Mul
+                | BinOpKind::Div
+                | BinOpKind::Rem
+                | BinOpKind::Lt
+                | BinOpKind::Gt
+                | BinOpKind::Le
+                | BinOpKind::Ge => self.eval_int_op(*left, *right, kind, span),
+                BinOpKind::FAdd
+                | BinOpKind::FSub
+                | BinOpKind::FMul
+                | BinOpKind::FDiv
+                | BinOpKind::FLt
+                | BinOpKind::FGt
+                | BinOpKind::FLe
+                | BinOpKind::FGe => self.eval_float_op(*left, *right, kind, span),
+                BinOpKind::Concat => match (self.eval(*left)?, self.eval(*right)?) {
+                    (BoundValue::Value(Value::Str(s)), BoundValue::Value(Value::Str(t))) => {
+                        Ok(BoundValue::Value(Value::Str(s + &t)))
+                    }
+                    _ => Err(RuntimeError::BadOperand {
+                        operation: kind.to_string(),
+                        expected: "Str operands".to_string(),
+                        span,
+                    }),
+                },
+                BinOpKind::Eql | BinOpKind::Neq => {
                     let left = self.eval(*left)?;
                     let right = self.eval(*right)?;
-                    Ok(BoundValue::Value(Value::Bool(Bool { b: left == right })))
+                    let equal = left == right;
+                    Ok(BoundValue::Value(Value::Bool(
+                        equal == (kind == BinOpKind::Eql),
+                    )))
+                }
+                BinOpKind::And | BinOpKind::Or => {
+                    // `&&` stops at the first false operand and `||` at the first true one
+                    let stop_at = kind == BinOpKind::Or;
+                    let operation = kind.to_string();
+                    match self.eval_bool(*left, &operation, span)? {
+                        b if b == stop_at => Ok(BoundValue::Value(Value::Bool(b))),
+                        _ => {
+                            let b = self.eval_bool(*right, &operation, span)?;
+                            Ok(BoundValue::Value(Value::Bool(b)))
+                        }
+                    }
                 }
             },
+            Expr::Not(Not { expr, span }) => {
+                let b = self.eval_bool(*expr, "not", span)?;
+                Ok(BoundValue::Value(Value::Bool(!b)))
+            }
             Expr::Fun(fun) => Ok(BoundValue::Value(Value::Fun {
                 fun,
                 env: self.env.clone(),
+                module: self.module,
             })),
-            Expr::App(App { fun, arg }) => match self.eval(*fun)? {
-                BoundValue::Value(Value::Fun {
-                    fun:
-                        Fun {
-                            arg: arg_name,
-                            body,
-                        },
-                    env: fun_env,
-                }) => {
-                    let arg = self.eval(*arg)?;
-
-                    // set evaluator env to the captured env of the closure, evaluate the body, and then set the env back
-                    let mut temp_env = fun_env;
-                    std::mem::swap(&mut self.env, &mut temp_env);
-                    let result = self.eval_with_binding(arg_name, arg, *body);
-                    std::mem::swap(&mut self.env, &mut temp_env);
-                    result
-                }
-                BoundValue::Value(Value::Builtin(builtin)) => {
-                    let arg = self.eval(*arg)?;
-                    self.builtins.eval(builtin, arg)
-                }
-                BoundValue::Thunk(expr) => self.eval(Expr::App(App {
-                    fun: Box::new(expr),
-                    arg,
-                })),
-                _ => Err("Cannot apply non-functions".to_string()),
-            },
+            Expr::App(App { fun, arg, span }) => {
+                let fun = self.eval(*fun)?;
+                let arg = self.eval(*arg)?;
+                self.apply(fun, arg, span)
+            }
             Expr::Let(Let {
                 name,
                 value,
                 body,
                 rec,
+                ..
             }) => {
                 let value = self.eval_let_value(name.clone(), *value, rec)?;
                 self.eval_with_binding(name, value, *body)
             }
-            Expr::If(If { check, then, els }) => {
+            Expr::If(If {
+                check, then, els, ..
+            }) => {
+                let check_span = check.span();
                 let check = self.eval(*check)?;
                 match check {
-                    BoundValue::Value(Value::Bool(Bool { b })) => {
+                    BoundValue::Value(Value::Bool(b)) => {
                         if b {
                             self.eval(*then)
                         } else {
                             self.eval(*els)
                         }
                     }
-                    _ => Err("If check must be Bool".to_string()),
+                    _ => Err(RuntimeError::BadOperand {
+                        operation: "If".to_string(),
+                        expected: "a Bool".to_string(),
+                        span: check_span,
+                    }),
+                }
+            }
+            Expr::Match(Match { expr, arms, .. }) => {
+                let expr_span = expr.span();
+                let value = self.eval(*expr)?;
+                for Arm { pattern, body, .. } in arms {
+                    let mut bindings = Vec::new();
+                    if match_pattern(&pattern, &value, &self.env, &mut bindings) {
+                        for (name, value) in &bindings {
+                            self.env.push_binding(name, value.clone());
+                        }
+                        let result = self.eval(*body);
+                        for (name, _) in &bindings {
+                            self.env.pop_binding(name);
+                        }
+                        return result;
+                    }
                 }
+                Err(RuntimeError::MatchFailure {
+                    value: match value {
+                        BoundValue::Value(value) => value.to_string(),
+                        BoundValue::Thunk(_) => "<function>".to_string(),
+                    },
+                    span: expr_span,
+                })
+            }
+            Expr::Ascribe(Ascribe { expr, .. }) => self.eval(*expr),
+        }
+    }
+
+    fn apply(
+        &mut self,
+        fun: BoundValue,
+        arg: BoundValue,
+        span: Span,
+    ) -> Result<BoundValue, RuntimeError> {
+        match fun {
+            BoundValue::Value(Value::Fun {
+                fun:
+                    Fun {
+                        arg: arg_name,
+                        body,
+                        ..
+                    },
+                env: fun_env,
+                module: fun_module,
+            }) => {
+                // set evaluator env to the captured env of the closure, evaluate the body, and then set the env back
+                let mut temp_env = fun_env;
+                std::mem::swap(&mut self.env, &mut temp_env);
+                let module = std::mem::replace(&mut self.module, fun_module);
+                let result = self.eval_with_binding(arg_name, arg, *body);
+                std::mem::swap(&mut self.env, &mut temp_env);
+                self.module = module;
+                // The innermost function an error happens in is the one whose code failed
+                if result.is_err() {
+                    self.failed_in.get_or_insert(fun_module);
+                }
+                result
+            }
+            BoundValue::Value(Value::Builtin {
+                name,
+                arity,
+                mut args,
+            }) => {
+                // A builtin collects its arguments until it has all of them, and is then run
+                args.push(self.force(arg)?);
+                if args.len() < arity {
+                    Ok(BoundValue::Value(Value::Builtin { name, arity, args }))
+                } else {
+                    self.eval_builtin(name, args, span)
+                }
+            }
+            BoundValue::Value(constructor @ Value::Constructor { .. }) => {
+                let field = self.force(arg)?;
+                Ok(BoundValue::Value(constructor.with_field(field)))
+            }
+            BoundValue::Thunk(expr) => {
+                let fun = self.eval(expr)?;
+                self.apply(fun, arg, span)
             }
+            _ => Err(RuntimeError::BadOperand {
+                operation: "Application".to_string(),
+                expected: "a function".to_string(),
+                span,
+            }),
         }
     }
 
-    fn eval_arith(
+    /// Runs a builtin that has been given all of its arguments. The ones that call functions they
+    /// were given need the evaluator, so they're run here rather than by [Builtins].
+    fn eval_builtin(
+        &mut self,
+        name: String,
+        args: Vec<Value>,
+        span: Span,
+    ) -> Result<BoundValue, RuntimeError> {
+        let not_a_list = |name: String| RuntimeError::Builtin {
+            name,
+            message: "Not a List".to_string(),
+            span,
+        };
+        if let Some(native) = self.registry.get(&name) {
+            return match native.call(&args) {
+                Ok(value) => Ok(BoundValue::Value(value)),
+                Err(message) => Err(RuntimeError::Builtin {
+                    name,
+                    message,
+                    span,
+                }),
+            };
+        }
+        match (name.as_str(), args.as_slice()) {
+            ("map", [f, xs]) => {
+                let Value::List(elems) = xs else {
+                    return Err(not_a_list(name));
+                };
+                let elems = elems
+                    .iter()
+                    .map(|elem| {
+                        let fun = BoundValue::Value(f.clone());
+                        let elem = BoundValue::Value(elem.clone());
+                        check_fully_evaluated(self.apply(fun, elem, span)?)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(BoundValue::Value(Value::List(elems)))
+            }
+            ("fold", [f, init, xs]) => {
+                let Value::List(elems) = xs else {
+                    return Err(not_a_list(name));
+                };
+                elems
+                    .iter()
+                    .try_fold(BoundValue::Value(init.clone()), |acc, elem| {
+                        let partial = self.apply(BoundValue::Value(f.clone()), acc, span)?;
+                        self.apply(partial, BoundValue::Value(elem.clone()), span)
+                    })
+            }
+            _ => self.builtins.eval(name, args, span),
+        }
+    }
+
+    /// Evaluates a value bound by `let rec`, which is only evaluated when it's used
+    fn force(&mut self, value: BoundValue) -> Result<Value, RuntimeError> {
+        match value {
+            BoundValue::Value(value) => Ok(value),
+            BoundValue::Thunk(expr) => check_fully_evaluated(self.eval(expr)?),
+        }
+    }
+
+    /// Evaluates an operator whose operands are Ints
+    fn eval_int_op(
         &mut self,
         left: Expr,
         right: Expr,
-        f: fn(i64, i64) -> i64,
-    ) -> Result<BoundValue, String> {
+        kind: BinOpKind,
+        span: Span,
+    ) -> Result<BoundValue, RuntimeError> {
+        let right_span = right.span();
         let (x, y) = match (self.eval(left)?, self.eval(right)?) {
-            (
-                BoundValue::Value(Value::Int(Int { n: x })),
-                BoundValue::Value(Value::Int(Int { n: y })),
-            ) => Ok((x, y)),
-            _ => Err("Cannot eval BinOp with non-Int operands"),
+            (BoundValue::Value(Value::Int(x)), BoundValue::Value(Value::Int(y))) => Ok((x, y)),
+            _ => Err(RuntimeError::BadOperand {
+                operation: kind.to_string(),
+                expected: "Int operands".to_string(),
+                span,
+            }),
         }?;
 
-        Ok(BoundValue::Value(Value::Int(Int { n: f(x, y) })))
+        let value = match kind {
+            BinOpKind::Div | BinOpKind::Rem if y == 0 => {
+                return Err(RuntimeError::DivisionByZero { span: right_span })
+            }
+            BinOpKind::Add | BinOpKind::Sub | BinOpKind::Mul | BinOpKind::Div | BinOpKind::Rem => {
+                let result = match kind {
+                    BinOpKind::Add => x.checked_add(y),
+                    BinOpKind::Sub => x.checked_sub(y),
+                    BinOpKind::Mul => x.checked_mul(y),
+                    // Only the smallest Int divided by -1 overflows
+                    BinOpKind::Div => x.checked_div(y),
+                    _ => x.checked_rem(y),
+                };
+                Value::Int(result.ok_or(RuntimeError::IntegerOverflow { span })?)
+            }
+            BinOpKind::Lt => Value::Bool(x < y),
+            BinOpKind::Gt => Value::Bool(x > y),
+            BinOpKind::Le => Value::Bool(x <= y),
+            BinOpKind::Ge => Value::Bool(x >= y),
+            _ => unreachable!("{} doesn't take Int operands", kind),
+        };
+
+        Ok(BoundValue::Value(value))
+    }
+
+    /// Evaluates an operator whose operands are Floats. Unlike with Ints, dividing by zero gives
+    /// an infinity or NaN rather than failing.
+    fn eval_float_op(
+        &mut self,
+        left: Expr,
+        right: Expr,
+        kind: BinOpKind,
+        span: Span,
+    ) -> Result<BoundValue, RuntimeError> {
+        let (x, y) = match (self.eval(left)?, self.eval(right)?) {
+            (BoundValue::Value(Value::Float(x)), BoundValue::Value(Value::Float(y))) => Ok((x, y)),
+            _ => Err(RuntimeError::BadOperand {
+                operation: kind.to_string(),
+                expected: "Float operands".to_string(),
+                span,
+            }),
+        }?;
+
+        let value = match kind {
+            BinOpKind::FAdd => Value::Float(x + y),
+            BinOpKind::FSub => Value::Float(x - y),
+            BinOpKind::FMul => Value::Float(x * y),
+            BinOpKind::FDiv => Value::Float(x / y),
+            BinOpKind::FLt => Value::Bool(x < y),
+            BinOpKind::FGt => Value::Bool(x > y),
+            BinOpKind::FLe => Value::Bool(x <= y),
+            BinOpKind::FGe => Value::Bool(x >= y),
+            _ => unreachable!("{} doesn't take Float operands", kind),
+        };
+
+        Ok(BoundValue::Value(value))
+    }
+
+    /// Evaluates an operand of `operation` at `span`, which must be a Bool
+    fn eval_bool(&mut self, expr: Expr, operation: &str, span: Span) -> Result<bool, RuntimeError> {
+        match self.eval(expr)? {
+            BoundValue::Value(Value::Bool(b)) => Ok(b),
+            _ => Err(RuntimeError::BadOperand {
+                operation: operation.to_string(),
+                expected: "a Bool".to_string(),
+                span,
+            }),
+        }
+    }
+
+    fn eval_fields(&mut self, fields: Vec<Field>) -> Result<Vec<(String, Value)>, RuntimeError> {
+        fields
+            .into_iter()
+            .map(|Field { name, value, .. }| {
+                let value = self.eval(value)?;
+                Ok((name, self.force(value)?))
+            })
+            .collect()
     }
 
     fn eval_let_value(
@@ -157,7 +634,7 @@ impl<'a> Evaluator<'a> {
         name: String,
         value: Expr,
         rec: bool,
-    ) -> Result<BoundValue, String> {
+    ) -> Result<BoundValue, RuntimeError> {
         if rec {
             self.eval_with_binding(name, BoundValue::Thunk(value.clone()), value)
         } else {
@@ -170,10 +647,102 @@ impl<'a> Evaluator<'a> {
         name: String,
         value: BoundValue,
         expr: Expr,
-    ) -> Result<BoundValue, String> {
+    ) -> Result<BoundValue, RuntimeError> {
         self.env.push_binding(&name, value);
         let result = self.eval(expr);
         self.env.pop_binding(&name);
         result
     }
 }
+
+/// The name a constructor of `module` is known by in every module
+fn qualify(module: Option<&str>, name: &str) -> String {
+    match module {
+        Some(module) => format!("{}.{}", module, name),
+        None => name.to_string(),
+    }
+}
+
+/// The name every module knows the constructor that `name` refers to in `env` by. It may be one
+/// the module defines, an imported one, or one from the prelude.
+fn resolve_constructor(env: &Env, name: &str) -> Option<String> {
+    match env.lookup(name)? {
+        BoundValue::Value(Value::Constructor { name, .. }) => Some(name),
+        BoundValue::Value(Value::Data { constructor, .. }) => Some(constructor),
+        _ => None,
+    }
+}
+
+/// Whether `value` matches `pattern`, adding the variables the pattern binds to `bindings`.
+/// `env` is the scope the pattern is in, which decides which constructors it names.
+fn match_pattern(
+    pattern: &Pattern,
+    value: &BoundValue,
+    env: &Env,
+    bindings: &mut Vec<(String, BoundValue)>,
+) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard(_), _) => true,
+        (Pattern::Var(pattern::Var { name, .. }), _) => {
+            bindings.push((name.clone(), value.clone()));
+            true
+        }
+        (Pattern::Int(pattern::Int { n, .. }), BoundValue::Value(Value::Int(m))) => n == m,
+        (Pattern::Str(pattern::Str { s, .. }), BoundValue::Value(Value::Str(t))) => s == t,
+        (Pattern::Unit(_), BoundValue::Value(Value::Unit)) => true,
+        (Pattern::Bool(pattern::Bool { b, .. }), BoundValue::Value(Value::Bool(c))) => b == c,
+        (
+            Pattern::Constructor(pattern::Constructor { name, args, .. }),
+            BoundValue::Value(Value::Data {
+                constructor,
+                fields,
+            }),
+        ) => {
+            resolve_constructor(env, name).as_ref() == Some(constructor)
+                && args.len() == fields.len()
+                && args.iter().zip(fields).all(|(arg, field)| {
+                    match_pattern(arg, &BoundValue::Value(field.clone()), env, bindings)
+                })
+        }
+        (
+            Pattern::Record(pattern::Record { fields, .. }),
+            BoundValue::Value(Value::Record(values)),
+        ) => fields.iter().all(|field| match values.get(&field.name) {
+            Some(value) => match_pattern(
+                &field.pattern,
+                &BoundValue::Value(value.clone()),
+                env,
+                bindings,
+            ),
+            None => false,
+        }),
+        (Pattern::List(pattern::List { elems, .. }), BoundValue::Value(Value::List(values))) => {
+            elems.len() == values.len()
+                && elems.iter().zip(values).all(|(elem, value)| {
+                    match_pattern(elem, &BoundValue::Value(value.clone()), env, bindings)
+                })
+        }
+        (
+            Pattern::Cons(pattern::Cons { head, tail, .. }),
+            BoundValue::Value(Value::List(values)),
+        ) => match values.split_first() {
+            Some((first, rest)) => {
+                match_pattern(head, &BoundValue::Value(first.clone()), env, bindings)
+                    && match_pattern(
+                        tail,
+                        &BoundValue::Value(Value::List(rest.to_vec())),
+                        env,
+                        bindings,
+                    )
+            }
+            None => false,
+        },
+        (Pattern::Tuple(pattern::Tuple { elems, .. }), BoundValue::Value(Value::Tuple(values))) => {
+            elems.len() == values.len()
+                && elems.iter().zip(values).all(|(elem, value)| {
+                    match_pattern(elem, &BoundValue::Value(value.clone()), env, bindings)
+                })
+        }
+        _ => false,
+    }
+}
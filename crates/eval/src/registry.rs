@@ -0,0 +1,196 @@
+//! Builtins provided by the program embedding the evaluator, rather than by pandalang itself
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use pandalang_parser::{
+    ast::{
+        stmt::{Stmt, TypeDef},
+        types::{self, Type},
+    },
+    prelude,
+};
+
+use crate::value::Value;
+
+/// The Rust function behind a builtin. It is given all of the builtin's arguments at once, and
+/// an error is reported as the builtin failing with that message.
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, String>;
+
+/// A builtin registered by the host
+#[derive(Clone)]
+pub struct Native {
+    /// The pandalang type of the builtin
+    pub signature: Type,
+    /// How many arguments it takes, which is how many arrows its type has at the top level
+    pub arity: usize,
+    fun: Rc<NativeFn>,
+}
+
+impl Native {
+    pub fn call(&self, args: &[Value]) -> Result<Value, String> {
+        (self.fun)(args)
+    }
+}
+
+/// Builtins that the host makes available to the programs it runs, by name. The type checker
+/// needs their [signatures](Registry::signatures), and the
+/// [Evaluator](crate::Evaluator::with_registry) needs the registry itself to run them.
+#[derive(Clone, Default)]
+pub struct Registry {
+    natives: BTreeMap<String, Native>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `fun` as the builtin `name`, whose type is given by `signature` in pandalang
+    /// syntax, e.g. `Str -> Int -> Str`. The signature can use the built-in types and those of the
+    /// prelude. Registering a name again replaces the earlier builtin.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        signature: &str,
+        fun: impl Fn(&[Value]) -> Result<Value, String> + 'static,
+    ) -> Result<(), RegistryError> {
+        let name = name.into();
+        let signature = match pandalang_parser::parse_type(signature) {
+            Ok(signature) => *signature,
+            Err(error) => return Err(RegistryError::Parse { name, error }),
+        };
+        check_signature(&name, &signature)?;
+        let native = Native {
+            arity: signature.arity(),
+            signature,
+            fun: Rc::new(fun),
+        };
+        self.natives.insert(name, native);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Native> {
+        self.natives.get(name)
+    }
+
+    pub fn natives(&self) -> impl Iterator<Item = (&String, &Native)> {
+        self.natives.iter()
+    }
+
+    /// The type of every registered builtin, which is what the type checker needs to know about
+    /// them
+    pub fn signatures(&self) -> Vec<(String, Type)> {
+        self.natives
+            .iter()
+            .map(|(name, native)| (name.clone(), native.signature.clone()))
+            .collect()
+    }
+}
+
+/// Why a builtin couldn't be registered
+#[derive(Debug)]
+pub enum RegistryError {
+    /// The signature isn't a type
+    Parse {
+        name: String,
+        error: pandalang_parser::error::Error,
+    },
+    /// The signature names a type that isn't built in or defined by the prelude
+    UnknownType { name: String, type_name: String },
+    /// The signature applies a type to the wrong number of arguments
+    TypeArity {
+        name: String,
+        type_name: String,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::Parse { name, error } => {
+                write!(
+                    f,
+                    "The signature of builtin {} doesn't parse: {}",
+                    name, error
+                )
+            }
+            RegistryError::UnknownType { name, type_name } => write!(
+                f,
+                "The signature of builtin {} uses unknown type {}",
+                name, type_name
+            ),
+            RegistryError::TypeArity {
+                name,
+                type_name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "In the signature of builtin {}, type {} expects {} arguments, found {}",
+                name, type_name, expected, found
+            ),
+        }
+    }
+}
+
+/// Checks that every type `signature` names exists and is given as many arguments as it takes,
+/// so that mistakes are blamed on the builtin rather than on the program using it
+fn check_signature(name: &str, signature: &Type) -> Result<(), RegistryError> {
+    let mut arities: HashMap<&str, usize> = HashMap::from([
+        ("Int", 0),
+        ("Float", 0),
+        ("Str", 0),
+        ("Unit", 0),
+        ("Bool", 0),
+        ("List", 1),
+    ]);
+    let program = prelude::program();
+    for stmt in &program.stmts {
+        if let Stmt::TypeDef(TypeDef { name, params, .. }) = stmt {
+            arities.insert(name, params.len());
+        }
+    }
+    check_type(name, signature, &arities)
+}
+
+fn check_type(name: &str, typ: &Type, arities: &HashMap<&str, usize>) -> Result<(), RegistryError> {
+    let (type_name, args) = match typ {
+        Type::Simple(types::Simple { name, .. }) => (name, &[][..]),
+        Type::App(types::App { name, args, .. }) => (name, args.as_slice()),
+        Type::Var(_) => return Ok(()),
+        Type::Tuple(types::Tuple { elems, .. }) => {
+            return elems
+                .iter()
+                .try_for_each(|elem| check_type(name, elem, arities))
+        }
+        Type::Record(types::Record { fields, .. }) => {
+            return fields
+                .iter()
+                .try_for_each(|field| check_type(name, &field.typ, arities))
+        }
+        Type::Fun(types::Fun { from, to, .. }) => {
+            check_type(name, from, arities)?;
+            return check_type(name, to, arities);
+        }
+    };
+    match arities.get(type_name.as_str()) {
+        None => Err(RegistryError::UnknownType {
+            name: name.to_string(),
+            type_name: type_name.clone(),
+        }),
+        Some(&expected) if expected != args.len() => Err(RegistryError::TypeArity {
+            name: name.to_string(),
+            type_name: type_name.clone(),
+            expected,
+            found: args.len(),
+        }),
+        Some(_) => args
+            .iter()
+            .try_for_each(|arg| check_type(name, arg, arities)),
+    }
+}
@@ -1,23 +1,130 @@
-use pandalang_parser::ast::expr::{Bool, Fun, Int, Str};
+use std::collections::BTreeMap;
+
+use pandalang_parser::{ast::expr::Fun, module::ModuleId, prelude::unqualify};
 
 use crate::env::Env;
 
 #[derive(Clone)]
 pub enum Value {
-    Int(Int),
-    Str(Str),
+    Int(i64),
+    Float(f64),
+    Str(String),
     Unit,
-    Bool(Bool),
-    Fun { fun: Fun, env: Env },
-    Builtin(String),
+    Bool(bool),
+    Fun {
+        fun: Fun,
+        env: Env,
+        /// The module the function was defined in
+        module: ModuleId,
+    },
+    /// A builtin function, with the arguments it has been given so far. It is run once it has
+    /// been given `arity` of them.
+    Builtin {
+        name: String,
+        arity: usize,
+        args: Vec<Value>,
+    },
+    /// A constructor of a user-defined type that hasn't been given all of its fields yet
+    Constructor {
+        name: String,
+        arity: usize,
+        fields: Vec<Value>,
+    },
+    /// A value of a user-defined type, e.g. `Some 1`
+    Data {
+        constructor: String,
+        fields: Vec<Value>,
+    },
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    Record(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The value of a builtin that has been given no arguments so far
+    pub fn builtin(name: String, arity: usize) -> Value {
+        Value::Builtin {
+            name,
+            arity,
+            args: Vec::new(),
+        }
+    }
+
+    /// The value of a constructor that has been given no fields so far
+    pub fn constructor(name: String, arity: usize) -> Value {
+        Value::Constructor {
+            name,
+            arity,
+            fields: Vec::new(),
+        }
+        .saturate()
+    }
+
+    /// Gives a constructor one more field
+    pub fn with_field(self, field: Value) -> Value {
+        match self {
+            Value::Constructor {
+                name,
+                arity,
+                mut fields,
+            } => {
+                fields.push(field);
+                Value::Constructor {
+                    name,
+                    arity,
+                    fields,
+                }
+                .saturate()
+            }
+            _ => self,
+        }
+    }
+
+    /// Turns a constructor that has all of its fields into the value it constructs
+    fn saturate(self) -> Value {
+        match self {
+            Value::Constructor {
+                name,
+                arity,
+                fields,
+            } if fields.len() == arity => Value::Data {
+                constructor: name,
+                fields,
+            },
+            _ => self,
+        }
+    }
 }
 
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Int(l0), Self::Int(r0)) => l0 == r0,
+            (Self::Float(l0), Self::Float(r0)) => l0 == r0,
             (Self::Str(l0), Self::Str(r0)) => l0 == r0,
             (Self::Bool(l0), Self::Bool(r0)) => l0 == r0,
+            (Self::Unit, Self::Unit) => true,
+            (
+                Self::Data {
+                    constructor: l0,
+                    fields: l1,
+                },
+                Self::Data {
+                    constructor: r0,
+                    fields: r1,
+                },
+            ) => l0 == r0 && l1 == r1,
+            // Builtins are equal when they are the same builtin applied to equal arguments
+            (
+                Self::Builtin {
+                    name: l0, args: l1, ..
+                },
+                Self::Builtin {
+                    name: r0, args: r1, ..
+                },
+            ) => l0 == r0 && l1 == r1,
+            (Self::Tuple(l0), Self::Tuple(r0)) | (Self::List(l0), Self::List(r0)) => l0 == r0,
+            (Self::Record(l0), Self::Record(r0)) => l0 == r0,
             _ => false,
         }
     }
@@ -26,29 +133,129 @@ impl PartialEq for Value {
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Value::Int(Int { n }) => write!(f, "{}", n),
-            Value::Str(Str { s }) => write!(f, "{}", s),
+            Value::Int(n) => write!(f, "{}", n),
+            // Unlike Display, Debug always includes a decimal point or exponent
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Str(s) => write!(f, "{}", s),
             Value::Unit => write!(f, "()"),
-            Value::Bool(Bool { b }) => write!(f, "{}", b),
+            Value::Bool(b) => write!(f, "{}", b),
             Value::Fun { .. } => write!(f, "<function>"),
-            Value::Builtin(_) => write!(f, "<builtin>"),
+            // A partially applied builtin shows the arguments it has and a `_` for each missing one,
+            // e.g. `<builtin append [1] _>`
+            Value::Builtin { name, arity, args } => {
+                write!(f, "<builtin {}", name)?;
+                for arg in args {
+                    write!(f, " ")?;
+                    write_arg(f, arg)?;
+                }
+                for _ in args.len()..*arity {
+                    write!(f, " _")?;
+                }
+                write!(f, ">")
+            }
+            Value::Constructor { .. } => write!(f, "<constructor>"),
+            Value::Data {
+                constructor,
+                fields,
+            } => {
+                write!(f, "{}", unqualify(constructor))?;
+                for field in fields {
+                    write!(f, " ")?;
+                    write_arg(f, field)?;
+                }
+                Ok(())
+            }
+            Value::Tuple(elems) => {
+                write!(f, "(")?;
+                write_elems(f, elems)?;
+                write!(f, ")")
+            }
+            Value::List(elems) => {
+                write!(f, "[")?;
+                write_elems(f, elems)?;
+                write!(f, "]")
+            }
+            Value::Record(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    match value {
+                        Value::Str(s) => write!(f, "{} = {:?}", name, s)?,
+                        _ => write!(f, "{} = {}", name, value)?,
+                    }
+                }
+                write!(f, " }}")
+            }
         }
     }
 }
 
+/// Writes a field of a constructor or an argument of a builtin, parenthesizing it if it is itself
+/// a constructor with fields and quoting it if it is a string
+fn write_arg(f: &mut std::fmt::Formatter<'_>, arg: &Value) -> std::fmt::Result {
+    match arg {
+        Value::Data { fields, .. } if !fields.is_empty() => write!(f, "({})", arg),
+        Value::Str(s) => write!(f, "{:?}", s),
+        _ => write!(f, "{}", arg),
+    }
+}
+
+/// Writes the elements of a tuple or list separated by commas, quoting strings
+fn write_elems(f: &mut std::fmt::Formatter<'_>, elems: &[Value]) -> std::fmt::Result {
+    for (i, elem) in elems.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        match elem {
+            Value::Str(s) => write!(f, "{:?}", s)?,
+            _ => write!(f, "{}", elem)?,
+        }
+    }
+    Ok(())
+}
+
 impl std::fmt::Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Int(n) => f.debug_tuple("Int").field(n).finish(),
+            Self::Float(x) => f.debug_tuple("Float").field(x).finish(),
             Self::Str(s) => f.debug_tuple("Str").field(s).finish(),
             Self::Unit => f.debug_tuple("Unit").finish(),
             Self::Bool(b) => f.debug_tuple("Bool").field(b).finish(),
-            Self::Fun { fun, env: _ } => f
+            Self::Fun { fun, .. } => f
                 .debug_struct("Fun")
                 .field("fun", fun)
                 .field("env", &"<opaque>".to_string())
                 .finish(),
-            Self::Builtin(name) => f.debug_tuple("Builtin").field(name).finish(),
+            Self::Builtin { name, arity, args } => f
+                .debug_struct("Builtin")
+                .field("name", name)
+                .field("arity", arity)
+                .field("args", args)
+                .finish(),
+            Self::Constructor {
+                name,
+                arity,
+                fields,
+            } => f
+                .debug_struct("Constructor")
+                .field("name", name)
+                .field("arity", arity)
+                .field("fields", fields)
+                .finish(),
+            Self::Data {
+                constructor,
+                fields,
+            } => f
+                .debug_struct("Data")
+                .field("constructor", constructor)
+                .field("fields", fields)
+                .finish(),
+            Self::Tuple(elems) => f.debug_tuple("Tuple").field(elems).finish(),
+            Self::List(elems) => f.debug_tuple("List").field(elems).finish(),
+            Self::Record(fields) => f.debug_tuple("Record").field(fields).finish(),
         }
     }
 }
@@ -1,35 +1,129 @@
+use crate::span::Span;
+
+use super::{pattern::Pattern, types::Type};
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Expr {
     Int(Int),
+    Float(Float),
     Str(Str),
-    Unit,
+    Unit(Unit),
     Bool(Bool),
     Var(Var),
+    Constructor(Constructor),
+    Tuple(Tuple),
+    List(List),
+    Cons(Cons),
+    Record(Record),
+    Access(Access),
+    Update(Update),
     BinOp(BinOp),
+    Not(Not),
     Let(Let),
     Fun(Fun),
     App(App),
     If(If),
+    Match(Match),
+    Ascribe(Ascribe),
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Int {
     pub n: i64,
+    pub span: Span,
+}
+
+/// e.g. `1.5` or `1e-3`
+#[derive(PartialEq, Debug, Clone)]
+pub struct Float {
+    pub x: f64,
+    pub span: Span,
 }
 
+// A literal is never NaN, so equality is reflexive
+impl Eq for Float {}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Str {
     pub s: String,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Unit {
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Bool {
     pub b: bool,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Var {
     pub name: String,
+    pub span: Span,
+}
+
+/// A reference to a constructor of an algebraic data type, e.g. `Some`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Constructor {
+    pub name: String,
+    pub span: Span,
+}
+
+/// e.g. `(1, "one")`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Tuple {
+    pub elems: Vec<Expr>,
+    pub span: Span,
+}
+
+/// e.g. `[1, 2, 3]`, or `[]` for the empty list
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct List {
+    pub elems: Vec<Expr>,
+    pub span: Span,
+}
+
+/// `head :: tail`, the list `tail` with `head` added to the front
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Cons {
+    pub head: Box<Expr>,
+    pub tail: Box<Expr>,
+    pub span: Span,
+}
+
+/// e.g. `{ name = "x"; age = 3 }`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Record {
+    pub fields: Vec<Field>,
+    pub span: Span,
+}
+
+/// A field of a record and its value, e.g. `age = 3`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// e.g. `r.name`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Access {
+    pub expr: Box<Expr>,
+    pub field: String,
+    pub span: Span,
+}
+
+/// A copy of a record with some of its fields replaced, e.g. `{ r with age = 4 }`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Update {
+    pub expr: Box<Expr>,
+    pub fields: Vec<Field>,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -37,6 +131,14 @@ pub struct BinOp {
     pub left: Box<Expr>,
     pub right: Box<Expr>,
     pub kind: BinOpKind,
+    pub span: Span,
+}
+
+/// `not expr`, the negation of a Bool
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Not {
+    pub expr: Box<Expr>,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -45,18 +147,22 @@ pub struct Let {
     pub value: Box<Expr>,
     pub body: Box<Expr>,
     pub rec: bool,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Fun {
     pub arg: String,
+    pub arg_typ: Option<Type>,
     pub body: Box<Expr>,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct App {
     pub fun: Box<Expr>,
     pub arg: Box<Expr>,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -64,6 +170,92 @@ pub struct If {
     pub check: Box<Expr>,
     pub then: Box<Expr>,
     pub els: Box<Expr>,
+    pub span: Span,
+}
+
+/// `match expr with | pattern -> body | ...`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Match {
+    pub expr: Box<Expr>,
+    pub arms: Vec<Arm>,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub body: Box<Expr>,
+    pub span: Span,
+}
+
+/// An expression annotated with its type, e.g. `(x : Int)`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Ascribe {
+    pub expr: Box<Expr>,
+    pub typ: Type,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Int(Int { span, .. })
+            | Expr::Float(Float { span, .. })
+            | Expr::Str(Str { span, .. })
+            | Expr::Unit(Unit { span })
+            | Expr::Bool(Bool { span, .. })
+            | Expr::Var(Var { span, .. })
+            | Expr::Constructor(Constructor { span, .. })
+            | Expr::Tuple(Tuple { span, .. })
+            | Expr::List(List { span, .. })
+            | Expr::Cons(Cons { span, .. })
+            | Expr::Record(Record { span, .. })
+            | Expr::Access(Access { span, .. })
+            | Expr::Update(Update { span, .. })
+            | Expr::BinOp(BinOp { span, .. })
+            | Expr::Not(Not { span, .. })
+            | Expr::Let(Let { span, .. })
+            | Expr::Fun(Fun { span, .. })
+            | Expr::App(App { span, .. })
+            | Expr::If(If { span, .. })
+            | Expr::Match(Match { span, .. })
+            | Expr::Ascribe(Ascribe { span, .. }) => *span,
+        }
+    }
+}
+
+/// Desugars the optional annotation in `let x : T = e` onto the value, giving `let x = (e : T)`
+pub(crate) fn ascribe(expr: Box<Expr>, typ: Option<Box<Type>>) -> Box<Expr> {
+    match typ {
+        Some(typ) => {
+            let span = typ.span().merge(expr.span());
+            Box::new(Expr::Ascribe(Ascribe {
+                expr,
+                typ: *typ,
+                span,
+            }))
+        }
+        None => expr,
+    }
+}
+
+/// Desugars `let (x, y) = e in body` into `match e with | (x, y) -> body`
+pub(crate) fn destructure(
+    pattern: Pattern,
+    value: Box<Expr>,
+    body: Box<Expr>,
+    span: Span,
+) -> Box<Expr> {
+    let arm_span = pattern.span().merge(body.span());
+    Box::new(Expr::Match(Match {
+        expr: value,
+        arms: vec![Arm {
+            pattern,
+            body,
+            span: arm_span,
+        }],
+        span,
+    }))
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -74,18 +266,54 @@ pub enum BinOpKind {
     Div,
     Eql,
     Rem,
+    /// `+.`, and likewise the other operators ending in `.`, which take Floats
+    FAdd,
+    FSub,
+    FMul,
+    FDiv,
+    FLt,
+    FGt,
+    FLe,
+    FGe,
+    Neq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    /// `&&`, which only evaluates its right operand if the left one is true
+    And,
+    /// `||`, which only evaluates its right operand if the left one is false
+    Or,
+    /// `++`, which joins two Strs
+    Concat,
 }
 
-impl ToString for BinOpKind {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for BinOpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
             BinOpKind::Add => "+",
             BinOpKind::Sub => "-",
             BinOpKind::Mul => "*",
             BinOpKind::Div => "/",
             BinOpKind::Eql => "==",
             BinOpKind::Rem => "%",
-        }
-        .to_string()
+            BinOpKind::FAdd => "+.",
+            BinOpKind::Concat => "++",
+            BinOpKind::FSub => "-.",
+            BinOpKind::FMul => "*.",
+            BinOpKind::FDiv => "/.",
+            BinOpKind::FLt => "<.",
+            BinOpKind::FGt => ">.",
+            BinOpKind::FLe => "<=.",
+            BinOpKind::FGe => ">=.",
+            BinOpKind::Neq => "!=",
+            BinOpKind::Lt => "<",
+            BinOpKind::Gt => ">",
+            BinOpKind::Le => "<=",
+            BinOpKind::Ge => ">=",
+            BinOpKind::And => "&&",
+            BinOpKind::Or => "||",
+        };
+        write!(f, "{}", s)
     }
 }
@@ -1,6 +1,7 @@
 use self::stmt::Stmt;
 
 pub mod expr;
+pub mod pattern;
 pub mod stmt;
 pub mod types;
 
@@ -0,0 +1,141 @@
+use crate::span::Span;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Pattern {
+    Wildcard(Wildcard),
+    Var(Var),
+    Int(Int),
+    Str(Str),
+    Unit(Unit),
+    Bool(Bool),
+    Constructor(Constructor),
+    Tuple(Tuple),
+    List(List),
+    Cons(Cons),
+    Record(Record),
+}
+
+/// `_`, which matches anything without binding it
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Wildcard {
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Var {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Int {
+    pub n: i64,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Str {
+    pub s: String,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Unit {
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Bool {
+    pub b: bool,
+    pub span: Span,
+}
+
+/// A constructor applied to a pattern for each of its fields, e.g. `Cons x _`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Constructor {
+    pub name: String,
+    pub args: Vec<Pattern>,
+    pub span: Span,
+}
+
+/// A pattern for each element of a tuple, e.g. `(x, _)`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Tuple {
+    pub elems: Vec<Pattern>,
+    pub span: Span,
+}
+
+/// A pattern for each element of a list of exactly that length, e.g. `[x, y]`, or `[]` for the
+/// empty list
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct List {
+    pub elems: Vec<Pattern>,
+    pub span: Span,
+}
+
+/// A pattern for the first element of a non-empty list and a pattern for the rest of it, e.g.
+/// `x :: xs`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Cons {
+    pub head: Box<Pattern>,
+    pub tail: Box<Pattern>,
+    pub span: Span,
+}
+
+/// A pattern for some of the fields of a record, e.g. `{ name = n }`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Record {
+    pub fields: Vec<Field>,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub pattern: Pattern,
+    pub span: Span,
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard(Wildcard { span })
+            | Pattern::Var(Var { span, .. })
+            | Pattern::Int(Int { span, .. })
+            | Pattern::Str(Str { span, .. })
+            | Pattern::Unit(Unit { span })
+            | Pattern::Bool(Bool { span, .. })
+            | Pattern::Constructor(Constructor { span, .. })
+            | Pattern::Tuple(Tuple { span, .. })
+            | Pattern::List(List { span, .. })
+            | Pattern::Cons(Cons { span, .. })
+            | Pattern::Record(Record { span, .. }) => *span,
+        }
+    }
+
+    /// The names of the variables the pattern binds, from left to right
+    pub fn vars(&self) -> Vec<&str> {
+        match self {
+            Pattern::Var(Var { name, .. }) => vec![name],
+            Pattern::Wildcard(_)
+            | Pattern::Int(_)
+            | Pattern::Str(_)
+            | Pattern::Unit(_)
+            | Pattern::Bool(_) => Vec::new(),
+            Pattern::Constructor(Constructor { args: patterns, .. })
+            | Pattern::Tuple(Tuple {
+                elems: patterns, ..
+            })
+            | Pattern::List(List {
+                elems: patterns, ..
+            }) => patterns.iter().flat_map(Pattern::vars).collect(),
+            Pattern::Cons(Cons { head, tail, .. }) => {
+                head.vars().into_iter().chain(tail.vars()).collect()
+            }
+            Pattern::Record(Record { fields, .. }) => fields
+                .iter()
+                .flat_map(|field| field.pattern.vars())
+                .collect(),
+        }
+    }
+}
@@ -1,9 +1,14 @@
-use super::{expr::Expr, types::Type};
+use crate::span::Span;
+
+use super::{expr::Expr, pattern::Pattern, types::Type};
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Stmt {
     Let(Let),
+    LetPattern(LetPattern),
     Declare(Declare),
+    TypeDef(TypeDef),
+    Import(Import),
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -11,10 +16,64 @@ pub struct Let {
     pub name: String,
     pub value: Box<Expr>,
     pub rec: bool,
+    /// The doc comment right before the statement, without its `-- |` or `{-| -}` markers
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+/// A top-level `let` that destructures its value, e.g. `let (x, y) = (1, 2)`, defining each
+/// variable of the pattern
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LetPattern {
+    pub pattern: Pattern,
+    pub value: Box<Expr>,
+    /// The doc comment right before the statement, without its `-- |` or `{-| -}` markers
+    pub doc: Option<String>,
+    pub span: Span,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Declare {
     pub name: String,
     pub typ: Type,
+    /// The doc comment right before the statement, without its `-- |` or `{-| -}` markers
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+/// A definition of an algebraic data type, e.g. `type Option 'a = None | Some of 'a`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct TypeDef {
+    pub name: String,
+    /// The names of the type's parameters, without the leading `'`
+    pub params: Vec<String>,
+    pub constructors: Vec<ConstructorDef>,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ConstructorDef {
+    pub name: String,
+    pub fields: Vec<Type>,
+    pub span: Span,
+}
+
+/// `import List`, which makes the definitions of `List.panda` next to the importing file
+/// available as `List.map` and so on
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Import {
+    pub module: String,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Let(Let { span, .. })
+            | Stmt::LetPattern(LetPattern { span, .. })
+            | Stmt::Declare(Declare { span, .. })
+            | Stmt::TypeDef(TypeDef { span, .. })
+            | Stmt::Import(Import { span, .. }) => *span,
+        }
+    }
 }
@@ -1,12 +1,84 @@
-// TODO: syntax for polymorphic types
+use crate::span::Span;
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Type {
-    Simple(String),
+    Simple(Simple),
+    Var(Var),
+    App(App),
+    Tuple(Tuple),
+    Record(Record),
     Fun(Fun),
 }
 
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Simple {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A type variable, e.g. `'a`. The name doesn't include the leading `'`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Var {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A type constructor applied to arguments, e.g. `Option Int`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct App {
+    pub name: String,
+    pub args: Vec<Type>,
+    pub span: Span,
+}
+
+/// The type of tuples, e.g. `Int * Str`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Tuple {
+    pub elems: Vec<Type>,
+    pub span: Span,
+}
+
+/// The type of records with the given fields, e.g. `{ name : Str; age : Int }`. With a `rest`,
+/// e.g. `{ name : Str | 'r }`, it is the type of any record with at least those fields.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Record {
+    pub fields: Vec<Field>,
+    pub rest: Option<Var>,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub typ: Type,
+    pub span: Span,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Fun {
     pub from: Box<Type>,
     pub to: Box<Type>,
+    pub span: Span,
+}
+
+impl Type {
+    pub fn span(&self) -> Span {
+        match self {
+            Type::Simple(Simple { span, .. })
+            | Type::Var(Var { span, .. })
+            | Type::App(App { span, .. })
+            | Type::Tuple(Tuple { span, .. })
+            | Type::Record(Record { span, .. })
+            | Type::Fun(Fun { span, .. }) => *span,
+        }
+    }
+
+    /// How many arguments a function of this type takes before returning something that isn't a
+    /// function, e.g. 2 for `Int -> Int -> Int`
+    pub fn arity(&self) -> usize {
+        match self {
+            Type::Fun(Fun { to, .. }) => 1 + to.arity(),
+            _ => 0,
+        }
+    }
 }
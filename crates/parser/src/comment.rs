@@ -0,0 +1,157 @@
+// LALRPOP's lexer can't skip block comments, since they nest and so can't be matched by a regex.
+// Instead, comments are blanked out of the source before it is lexed. Every byte of a comment
+// except newlines becomes a space, so offsets into the stripped source are still offsets into
+// the original one.
+
+use crate::{
+    ast::{stmt, stmt::Stmt, Program},
+    error::{Error, ErrorKind},
+    span::Span,
+};
+
+/// A doc comment, e.g. `-- | Adds one` or `{-| Adds one -}`
+pub(crate) struct Doc {
+    pub text: String,
+    pub span: Span,
+}
+
+/// The source with its comments blanked out, and the doc comments that were in it
+pub(crate) struct Stripped {
+    pub source: String,
+    pub docs: Vec<Doc>,
+}
+
+pub(crate) fn strip(src: &str) -> Result<Stripped, Error> {
+    let bytes = src.as_bytes();
+    let mut source = bytes.to_vec();
+    let mut docs: Vec<Doc> = Vec::new();
+    // Whether the last doc comment was a line comment that the next line comment would continue,
+    // as nothing but whitespace has come since
+    let mut line_doc = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"--") {
+            let end = bytes[i..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |len| i + len);
+            let text = &src[i + 2..end];
+            match text.trim_start().strip_prefix('|') {
+                Some(doc) => {
+                    docs.push(Doc {
+                        text: doc.trim().to_string(),
+                        span: Span::new(i, end),
+                    });
+                    line_doc = true;
+                }
+                None if line_doc => {
+                    let doc = docs.last_mut().expect("a line doc comment was seen");
+                    doc.text.push('\n');
+                    doc.text.push_str(text.trim());
+                    doc.span.end = end;
+                }
+                None => {}
+            }
+            blank(&mut source[i..end]);
+            i = end;
+        } else if bytes[i..].starts_with(b"{-") {
+            let end = block_end(bytes, i).ok_or_else(|| {
+                Error::at(ErrorKind::UnterminatedComment, Span::new(i, i + 2), src)
+            })?;
+            if let Some(doc) = src[i + 2..end - 2].strip_prefix('|') {
+                let lines: Vec<_> = doc.trim().lines().map(str::trim).collect();
+                docs.push(Doc {
+                    text: lines.join("\n"),
+                    span: Span::new(i, end),
+                });
+            }
+            line_doc = false;
+            blank(&mut source[i..end]);
+            i = end;
+        } else if let Some(end) = string_end(bytes, i) {
+            // Comment markers in a string are part of the string
+            line_doc = false;
+            i = end;
+        } else {
+            line_doc &= bytes[i].is_ascii_whitespace();
+            i += 1;
+        }
+    }
+    let source = String::from_utf8(source).expect("only whole chars were blanked out");
+    Ok(Stripped { source, docs })
+}
+
+/// The offset just past the string literal starting at `start`, if one does
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let rest = &bytes[start..];
+    // An `r` at the end of an identifier doesn't start a raw string
+    let raw = start == 0 || !(bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_');
+    let (len, close) = if raw && rest.starts_with(b"r#\"") {
+        (3, &b"\"#"[..])
+    } else if raw && rest.starts_with(b"r\"") {
+        (2, &b"\""[..])
+    } else if rest.starts_with(b"\"") {
+        let mut i = start + 1;
+        while i < bytes.len() && bytes[i] != b'"' {
+            i += if bytes[i] == b'\\' { 2 } else { 1 };
+        }
+        return Some(i + 1);
+    } else {
+        return None;
+    };
+    let end = rest[len..]
+        .windows(close.len())
+        .position(|window| window == close)
+        .map_or(bytes.len(), |i| start + len + i + close.len());
+    Some(end)
+}
+
+/// The offset just past the `-}` that closes the block comment starting at `start`, taking
+/// nested block comments into account
+fn block_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"{-") {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"-}") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return Some(i);
+            }
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn blank(bytes: &mut [u8]) {
+    for b in bytes.iter_mut().filter(|b| **b != b'\n') {
+        *b = b' ';
+    }
+}
+
+/// Gives each top-level `let` and `declare` the doc comment right before it, if there is one
+pub(crate) fn attach_docs(program: &mut Program, stripped: &Stripped) {
+    for stmt in &mut program.stmts {
+        let (doc, span) = match stmt {
+            Stmt::Let(stmt::Let { doc, span, .. })
+            | Stmt::LetPattern(stmt::LetPattern { doc, span, .. })
+            | Stmt::Declare(stmt::Declare { doc, span, .. }) => (doc, *span),
+            Stmt::TypeDef(_) | Stmt::Import(_) => continue,
+        };
+        *doc = stripped
+            .docs
+            .iter()
+            .rfind(|doc| {
+                doc.span.end <= span.start
+                    && stripped.source[doc.span.end..span.start]
+                        .bytes()
+                        .all(|b| b.is_ascii_whitespace())
+            })
+            .map(|doc| doc.text.clone());
+    }
+}
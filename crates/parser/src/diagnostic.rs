@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use crate::span::{LineIndex, Span};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A message attached to a region of the source
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem with a program, independent of which phase found it.
+/// The first label is the primary one and determines the reported location.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Renders the diagnostic with an excerpt of `src` under each label, e.g.
+    ///
+    /// ```text
+    /// error: mismatched types
+    ///  --> 1:5
+    ///   |
+    /// 1 | 1 + "foo"
+    ///   |     ^^^^^ expected Int, found Str
+    ///   |
+    ///   = note: the operands of `+` are expected to have type Int
+    /// ```
+    pub fn render(&self, src: &str) -> String {
+        self.render_at(None, src)
+    }
+
+    /// Renders the diagnostic like [Diagnostic::render], with the location prefixed by the path
+    /// of the file `src` was read from, e.g. ` --> list.panda:1:5`
+    pub fn render_file(&self, path: &Path, src: &str) -> String {
+        self.render_at(Some(path), src)
+    }
+
+    fn render_at(&self, path: Option<&Path>, src: &str) -> String {
+        let index = LineIndex::new(src);
+        let gutter = self
+            .labels
+            .iter()
+            .map(|label| index.position(label.span.start).line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let pad = " ".repeat(gutter);
+
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+
+        for (i, label) in self.labels.iter().enumerate() {
+            let start = index.position(label.span.start);
+            let end = index.position(label.span.end);
+            let line = index.line(start.line);
+
+            if i == 0 {
+                match path {
+                    Some(path) => {
+                        out.push_str(&format!("{}--> {}:{}\n", pad, path.display(), start))
+                    }
+                    None => out.push_str(&format!("{}--> {}\n", pad, start)),
+                }
+            }
+
+            let width = if end.line == start.line {
+                end.column.saturating_sub(start.column)
+            } else {
+                (line.chars().count() + 1).saturating_sub(start.column)
+            };
+            let marker = if i == 0 { "^" } else { "-" };
+            let underline = format!(
+                "{}{}",
+                " ".repeat(start.column - 1),
+                marker.repeat(width.max(1))
+            );
+
+            out.push_str(&format!("{} |\n", pad));
+            out.push_str(&format!("{:>gutter$} | {}\n", start.line, line));
+            if label.message.is_empty() {
+                out.push_str(&format!("{} | {}\n", pad, underline));
+            } else {
+                out.push_str(&format!("{} | {} {}\n", pad, underline, label.message));
+            }
+        }
+
+        if !self.notes.is_empty() && !self.labels.is_empty() {
+            out.push_str(&format!("{} |\n", pad));
+        }
+
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n", pad, note));
+        }
+
+        out
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Renders each diagnostic against `src`, separated by blank lines
+pub fn render_all(diagnostics: impl IntoIterator<Item = Diagnostic>, src: &str) -> String {
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| diagnostic.render(src))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,193 @@
+use lalrpop_util::{lexer::Token, ParseError};
+
+use crate::{
+    diagnostic::Diagnostic,
+    span::{LineIndex, Position, Span},
+};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+    pub position: Position,
+}
+
+/// An error in a literal, found before the literal's position in the source is known
+#[derive(Debug)]
+pub struct LiteralError {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub enum ErrorKind {
+    InvalidToken,
+    UnexpectedEof {
+        expected: Vec<String>,
+    },
+    UnexpectedToken {
+        token: String,
+        expected: Vec<String>,
+    },
+    ExtraToken {
+        token: String,
+    },
+    /// A `{-` without a matching `-}`
+    UnterminatedComment,
+    /// A `\` in a string literal that isn't followed by a known escape
+    InvalidEscape {
+        escape: String,
+    },
+    /// A `\u` escape that isn't `\u{...}` with 1 to 6 hex digits naming a unicode scalar value
+    InvalidUnicodeEscape {
+        escape: String,
+    },
+    /// An integer literal too large in magnitude to be an Int
+    IntOutOfRange {
+        literal: String,
+    },
+    /// A float literal too large in magnitude to be a finite Float
+    FloatOutOfRange {
+        literal: String,
+    },
+}
+
+impl Error {
+    pub(crate) fn new(err: ParseError<usize, Token<'_>, LiteralError>, src: &str) -> Error {
+        let (kind, span) = match err {
+            ParseError::InvalidToken { location } => {
+                let len = src[location..].chars().next().map_or(0, char::len_utf8);
+                (ErrorKind::InvalidToken, Span::new(location, location + len))
+            }
+            ParseError::UnrecognizedEof { location, expected } => (
+                ErrorKind::UnexpectedEof {
+                    expected: describe_expected(expected),
+                },
+                Span::new(location, location),
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, Token(_, token), end),
+                expected,
+            } => (
+                ErrorKind::UnexpectedToken {
+                    token: token.to_string(),
+                    expected: describe_expected(expected),
+                },
+                Span::new(start, end),
+            ),
+            ParseError::ExtraToken {
+                token: (start, Token(_, token), end),
+            } => (
+                ErrorKind::ExtraToken {
+                    token: token.to_string(),
+                },
+                Span::new(start, end),
+            ),
+            ParseError::User { error } => (error.kind, error.span),
+        };
+        Error::at(kind, span, src)
+    }
+
+    pub(crate) fn at(kind: ErrorKind, span: Span, src: &str) -> Error {
+        let position = LineIndex::new(src).position(span.start);
+        Error {
+            kind,
+            span,
+            position,
+        }
+    }
+
+    fn message(&self) -> String {
+        match &self.kind {
+            ErrorKind::InvalidToken => "invalid token".to_string(),
+            ErrorKind::UnexpectedEof { .. } => "unexpected end of input".to_string(),
+            ErrorKind::UnexpectedToken { token, .. } => format!("unexpected `{}`", token),
+            ErrorKind::ExtraToken { token } => format!("extra token `{}`", token),
+            ErrorKind::UnterminatedComment => "unterminated block comment".to_string(),
+            ErrorKind::InvalidEscape { escape } => format!("invalid escape sequence `{}`", escape),
+            ErrorKind::InvalidUnicodeEscape { escape } => {
+                format!("invalid unicode escape `{}`", escape)
+            }
+            ErrorKind::IntOutOfRange { literal } => {
+                format!("integer literal `{}` is out of range", literal)
+            }
+            ErrorKind::FloatOutOfRange { literal } => {
+                format!("float literal `{}` is out of range", literal)
+            }
+        }
+    }
+
+    fn expected(&self) -> Option<String> {
+        match &self.kind {
+            ErrorKind::UnexpectedEof { expected } | ErrorKind::UnexpectedToken { expected, .. }
+                if !expected.is_empty() =>
+            {
+                Some(match expected.as_slice() {
+                    [only] => format!("expected {}", only),
+                    _ => format!("expected one of {}", expected.join(", ")),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = match self.kind {
+            ErrorKind::UnterminatedComment => "this comment is never closed".to_string(),
+            ErrorKind::InvalidEscape { .. } => {
+                r#"the escapes are `\n`, `\r`, `\t`, `\"`, `\\` and `\u{...}`"#.to_string()
+            }
+            ErrorKind::InvalidUnicodeEscape { .. } => {
+                "expected 1 to 6 hex digits naming a unicode scalar value, e.g. `\\u{e9}`"
+                    .to_string()
+            }
+            ErrorKind::IntOutOfRange { .. } => {
+                "Ints range from -9223372036854775808 to 9223372036854775807".to_string()
+            }
+            ErrorKind::FloatOutOfRange { .. } => {
+                "Floats can't be larger in magnitude than about 1.8e308".to_string()
+            }
+            _ => self.expected().unwrap_or_default(),
+        };
+        Diagnostic::error(self.message()).with_label(self.span, label)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.position, self.message())?;
+        if let Some(expected) = self.expected() {
+            write!(f, ", {}", expected)?;
+        }
+        Ok(())
+    }
+}
+
+/// LALRPOP reports expected terminals as they are written in the grammar, which for the regex
+/// terminals is the name the grammar's `match` block gives them. Give those a readable name
+/// instead.
+fn describe_expected(expected: Vec<String>) -> Vec<String> {
+    expected
+        .into_iter()
+        .map(|terminal| match terminal.as_str() {
+            "IDENT" => "identifier".to_string(),
+            "TYPE_NAME" => "type name".to_string(),
+            "QUALIFIED_NAME" => "qualified name".to_string(),
+            "QUALIFIED_TYPE_NAME" => "qualified type name".to_string(),
+            "TYPE_VAR" => "type variable".to_string(),
+            "INT" => "integer".to_string(),
+            "FLOAT" => "float".to_string(),
+            "STR" | "RAW_STR" | "HASH_STR" => "string".to_string(),
+            _ => match terminal.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+                Some(literal) => format!("`{}`", literal),
+                None => terminal,
+            },
+        })
+        .fold(Vec::new(), |mut expected, terminal| {
+            // The kinds of string literal are all described as "string"
+            if !expected.contains(&terminal) {
+                expected.push(terminal);
+            }
+            expected
+        })
+}
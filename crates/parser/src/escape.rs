@@ -0,0 +1,101 @@
+//! Escape sequences in string literals
+
+use crate::{
+    error::{ErrorKind, LiteralError},
+    span::Span,
+};
+
+/// Replaces the escape sequences in the body of a string literal with the chars they stand for.
+/// `offset` is where `body` starts in the source, so that errors can point at the bad escape.
+///
+/// The escapes are `\n`, `\r`, `\t`, `\"`, `\\` and `\u{...}` with 1 to 6 hex digits. A `\` at
+/// the end of a line skips the line break, whether it's `\n` or `\r\n`, and the indentation of
+/// the next line, so that a long string can be split over several lines.
+pub(crate) fn unescape(body: &str, offset: usize) -> Result<String, LiteralError> {
+    let mut s = String::with_capacity(body.len());
+    let mut chars = body.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            s.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some((_, 'n')) => '\n',
+            Some((_, 'r')) => '\r',
+            Some((_, 't')) => '\t',
+            Some((_, '"')) => '"',
+            Some((_, '\\')) => '\\',
+            Some((_, '\n')) => {
+                while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                continue;
+            }
+            // A line can also end in `\r\n`
+            Some((_, '\r')) if chars.next_if(|(_, c)| *c == '\n').is_some() => {
+                while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                continue;
+            }
+            Some((_, 'u')) => {
+                let (end, c) = unicode_escape(body, start);
+                let c = c.ok_or(LiteralError {
+                    kind: ErrorKind::InvalidUnicodeEscape {
+                        escape: body[start..end].to_string(),
+                    },
+                    span: Span::new(offset + start, offset + end),
+                })?;
+                while chars.next_if(|(i, _)| *i < end).is_some() {}
+                c
+            }
+            Some((i, c)) => {
+                let end = i + c.len_utf8();
+                return Err(LiteralError {
+                    kind: ErrorKind::InvalidEscape {
+                        escape: body[start..end].to_string(),
+                    },
+                    span: Span::new(offset + start, offset + end),
+                });
+            }
+            // The string token can't end in a lone `\`, as it would have escaped the closing quote
+            None => unreachable!("string literal ends in `\\`"),
+        };
+        s.push(escaped);
+    }
+    Ok(s)
+}
+
+/// Reads the `\u{...}` escape at `start`, returning where it ends and the char it stands for,
+/// if it's valid
+fn unicode_escape(body: &str, start: usize) -> (usize, Option<char>) {
+    let Some(digits) = body[start..].strip_prefix("\\u{") else {
+        return (start + 2, None);
+    };
+    let len = digits
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(digits.len());
+    let end = start + 3 + len;
+    if !digits[len..].starts_with('}') {
+        return (end, None);
+    }
+    let c = Some(&digits[..len])
+        .filter(|digits| (1..=6).contains(&digits.len()))
+        .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+        .and_then(char::from_u32);
+    (end + 1, c)
+}
+
+/// Writes `s` as the body of a string literal, escaping the chars that can't appear in one as
+/// they are
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,7 +1,16 @@
 pub mod ast;
+mod comment;
+pub mod diagnostic;
+pub mod error;
+pub mod escape;
+pub mod module;
+mod number;
+pub mod prelude;
+pub mod span;
 
 use ast::{expr::Expr, types::Type, Program};
-use lalrpop_util::{lalrpop_mod, lexer::Token, ParseError};
+use error::Error;
+use lalrpop_util::lalrpop_mod;
 
 lalrpop_mod!(
     #[allow(clippy::all)]
@@ -9,14 +18,39 @@ lalrpop_mod!(
     pub grammar
 );
 
-pub fn parse(s: &str) -> Result<Program, ParseError<usize, Token<'_>, &'static str>> {
-    grammar::ProgramParser::new().parse(s)
+/// Parses a whole program. The parser recovers from errors at statement boundaries, so all
+/// errors found are reported rather than just the first.
+pub fn parse(s: &str) -> Result<Program, Vec<Error>> {
+    let stripped = comment::strip(s).map_err(|err| vec![err])?;
+    let mut recovered = Vec::new();
+    let result = grammar::ProgramParser::new().parse(&mut recovered, &stripped.source);
+    let mut errors: Vec<Error> = recovered
+        .into_iter()
+        .map(|recovery| Error::new(recovery.error, s))
+        .collect();
+    match result {
+        Ok(mut program) if errors.is_empty() => {
+            comment::attach_docs(&mut program, &stripped);
+            Ok(program)
+        }
+        Ok(_) => Err(errors),
+        Err(err) => {
+            errors.push(Error::new(err, s));
+            Err(errors)
+        }
+    }
 }
 
-pub fn parse_expr(s: &str) -> Result<Box<Expr>, ParseError<usize, Token<'_>, &'static str>> {
-    grammar::ExprParser::new().parse(s)
+pub fn parse_expr(s: &str) -> Result<Box<Expr>, Error> {
+    let stripped = comment::strip(s)?;
+    grammar::ExprParser::new()
+        .parse(&mut Vec::new(), &stripped.source)
+        .map_err(|err| Error::new(err, s))
 }
 
-pub fn parse_type(s: &str) -> Result<Box<Type>, ParseError<usize, Token<'_>, &'static str>> {
-    grammar::TypeParser::new().parse(s)
+pub fn parse_type(s: &str) -> Result<Box<Type>, Error> {
+    let stripped = comment::strip(s)?;
+    grammar::TypeParser::new()
+        .parse(&mut Vec::new(), &stripped.source)
+        .map_err(|err| Error::new(err, s))
 }
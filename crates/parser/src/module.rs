@@ -0,0 +1,197 @@
+//! Programs split across several files, which `import` each other
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ast::{
+        stmt::{Import, Stmt},
+        Program,
+    },
+    diagnostic::Diagnostic,
+    prelude,
+};
+
+/// A file of a program
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Module {
+    /// The name the module is imported by, which qualifies its definitions in the modules that
+    /// import it. The file being run has no name.
+    pub name: Option<String>,
+    pub path: PathBuf,
+    pub src: String,
+    pub program: Program,
+}
+
+/// The file being run, together with every module it imports, directly or not
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ModuleGraph {
+    /// Each module comes after the modules it imports, so the file being run is last
+    pub modules: Vec<Module>,
+}
+
+/// The code a module of a program can run, which is either its own or that of the prelude
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum ModuleId {
+    Prelude,
+    /// The index of the module in [ModuleGraph::modules]
+    Graph(usize),
+}
+
+/// An error in one of the modules of a [ModuleGraph], or in the prelude
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub struct ModuleError<E> {
+    pub module: ModuleId,
+    pub error: E,
+}
+
+impl ModuleGraph {
+    /// Renders each diagnostic against the source of the module it is about, separated by blank
+    /// lines
+    pub fn render_all(
+        &self,
+        diagnostics: impl IntoIterator<Item = (ModuleId, Diagnostic)>,
+    ) -> String {
+        diagnostics
+            .into_iter()
+            .map(|(module, diagnostic)| match module {
+                ModuleId::Prelude => {
+                    diagnostic.render_file(Path::new(prelude::FILE), prelude::SOURCE)
+                }
+                ModuleId::Graph(i) => {
+                    let module = &self.modules[i];
+                    diagnostic.render_file(&module.path, &module.src)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Why the files of a program couldn't be loaded
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum LoadError {
+    /// The file being run couldn't be read
+    Io { path: PathBuf, message: String },
+    /// Problems with one of the files, e.g. parse errors or an import that can't be resolved
+    File {
+        path: PathBuf,
+        src: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+}
+
+impl LoadError {
+    pub fn render(&self) -> String {
+        match self {
+            LoadError::Io { path, message } => {
+                format!("error: couldn't read {}: {}\n", path.display(), message)
+            }
+            LoadError::File {
+                path,
+                src,
+                diagnostics,
+            } => diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.render_file(path, src))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Loads the program in the file at `path` along with the modules it imports. `import List`
+/// imports the file `List.panda` in the same directory as the file importing it.
+pub fn load(path: &Path) -> Result<ModuleGraph, LoadError> {
+    let src = fs::read_to_string(path).map_err(|err| LoadError::Io {
+        path: path.to_path_buf(),
+        message: err.to_string(),
+    })?;
+    let mut loader = Loader::default();
+    loader.load(None, path.to_path_buf(), src)?;
+    Ok(ModuleGraph {
+        modules: loader.modules,
+    })
+}
+
+#[derive(Default)]
+struct Loader {
+    modules: Vec<Module>,
+    /// The files loaded so far
+    loaded: HashSet<PathBuf>,
+    /// The files whose imports are being loaded, innermost last, with the name of each. Importing
+    /// one of them again would be a cycle.
+    loading: Vec<(PathBuf, String)>,
+}
+
+impl Loader {
+    fn load(&mut self, name: Option<String>, path: PathBuf, src: String) -> Result<(), LoadError> {
+        let file_error = |diagnostics| LoadError::File {
+            path: path.clone(),
+            src: src.clone(),
+            diagnostics,
+        };
+        let program = crate::parse(&src)
+            .map_err(|errs| file_error(errs.iter().map(|err| err.to_diagnostic()).collect()))?;
+
+        let display_name = name.clone().unwrap_or_else(|| {
+            let stem = path.file_stem().unwrap_or(path.as_os_str());
+            stem.to_string_lossy().into_owned()
+        });
+        self.loading.push((identity(&path), display_name));
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for stmt in &program.stmts {
+            let Stmt::Import(Import { module, span }) = stmt else {
+                continue;
+            };
+            let import_path = dir.join(format!("{}.panda", module));
+            let id = identity(&import_path);
+
+            if let Some(start) = self.loading.iter().position(|(path, _)| *path == id) {
+                let cycle: Vec<_> = self.loading[start..]
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .chain([module.as_str()])
+                    .collect();
+                let diagnostic = Diagnostic::error("import cycle")
+                    .with_label(*span, format!("`{}` is already being imported", module))
+                    .with_note(format!(
+                        "the modules import each other in a cycle: {}",
+                        cycle.join(" -> ")
+                    ));
+                return Err(file_error(vec![diagnostic]));
+            }
+            // A module imported by several others is only loaded once
+            if self.loaded.contains(&id) {
+                continue;
+            }
+
+            let import_src = fs::read_to_string(&import_path).map_err(|err| {
+                let diagnostic = Diagnostic::error(format!("can't find module `{}`", module))
+                    .with_label(
+                        *span,
+                        format!("couldn't read {}: {}", import_path.display(), err),
+                    );
+                file_error(vec![diagnostic])
+            })?;
+            self.load(Some(module.clone()), import_path, import_src)?;
+        }
+        let (id, _) = self.loading.pop().expect("the module was pushed above");
+        self.loaded.insert(id);
+        self.modules.push(Module {
+            name,
+            path,
+            src,
+            program,
+        });
+        Ok(())
+    }
+}
+
+/// What identifies a file, so that the same file reached by different paths is the same module
+fn identity(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,34 @@
+//! Number literals
+
+use std::str::FromStr;
+
+use crate::{
+    error::{ErrorKind, LiteralError},
+    span::Span,
+};
+
+/// The Int an integer literal stands for, where `literal` has a leading `-` if it's negated.
+/// The sign is part of the literal rather than negating it afterwards, so that the smallest Int
+/// can be written.
+pub(crate) fn int(literal: &str, span: Span) -> Result<i64, LiteralError> {
+    i64::from_str(literal).map_err(|_| LiteralError {
+        kind: ErrorKind::IntOutOfRange {
+            literal: literal.to_string(),
+        },
+        span,
+    })
+}
+
+/// The Float a float literal stands for. A literal too large for a Float is rejected rather than
+/// becoming an infinity.
+pub(crate) fn float(literal: &str, span: Span) -> Result<f64, LiteralError> {
+    match f64::from_str(literal) {
+        Ok(x) if x.is_finite() => Ok(x),
+        _ => Err(LiteralError {
+            kind: ErrorKind::FloatOutOfRange {
+                literal: literal.to_string(),
+            },
+            span,
+        }),
+    }
+}
@@ -0,0 +1,31 @@
+//! The definitions that are in scope in every program, written in pandalang
+
+use std::sync::OnceLock;
+
+use crate::ast::Program;
+
+/// The source of the prelude
+pub const SOURCE: &str = include_str!("prelude.panda");
+
+/// The file name diagnostics about the prelude are shown with
+pub const FILE: &str = "prelude.panda";
+
+/// What the types and constructors of the prelude are qualified by, the way an imported module's
+/// are. It isn't a valid module name, so they can't clash with a program's own, which shadow them.
+pub const MODULE: &str = "prelude";
+
+/// The name of a type or constructor as it's written in a program, i.e. without the qualifier of
+/// the prelude
+pub fn unqualify(name: &str) -> &str {
+    name.strip_prefix(MODULE)
+        .and_then(|name| name.strip_prefix('.'))
+        .unwrap_or(name)
+}
+
+/// Parses the prelude. It is checked and run before a program, unless that is opted out of.
+pub fn program() -> Program {
+    static PROGRAM: OnceLock<Program> = OnceLock::new();
+    PROGRAM
+        .get_or_init(|| crate::parse(SOURCE).expect("the prelude parses"))
+        .clone()
+}
@@ -1,4 +1,4 @@
-use pandalang_parser::ast::Program;
+use pandalang_parser::{ast::Program, diagnostic::render_all};
 use wasm_bindgen::prelude::wasm_bindgen;
 
 #[wasm_bindgen]
@@ -10,19 +10,33 @@ pub fn parse(source: &str) -> Result<String, String> {
 #[wasm_bindgen]
 pub fn typecheck(source: &str) -> Result<String, String> {
     let ast = parse_(source)?;
-    let types = pandalang_types::check_prog_to_strings(ast).map_err(|err| err.to_string())?;
-    Ok(format!("{:#?}", types))
+    let checked = pandalang_types::check_program(ast)
+        .map_err(|errs| render_all(errs.iter().map(|err| err.to_diagnostic()), source))?;
+    let types: Vec<_> = checked
+        .bindings
+        .into_iter()
+        .map(|(name, scheme)| (name, scheme.to_string()))
+        .collect();
+    let mut out = render_all(checked.warnings.iter().map(|w| w.to_diagnostic()), source);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(&format!("{:#?}", types));
+    Ok(out)
 }
 
 #[wasm_bindgen]
 pub fn run(source: &str) -> Result<String, String> {
     let ast = parse_(source)?;
-    pandalang_types::check_prog_to_strings(ast.clone()).map_err(|err| err.to_string())?;
+    pandalang_types::check_executable(ast.clone())
+        .map_err(|errs| render_all(errs.iter().map(|err| err.to_diagnostic()), source))?;
     let mut stdout = Vec::new();
-    pandalang_eval::run_program(ast, &mut stdout)?;
+    pandalang_eval::run_program(ast, &mut stdout)
+        .map_err(|err| err.to_diagnostic().render(source))?;
     Ok(String::from_utf8_lossy(&stdout).to_string())
 }
 
 fn parse_(source: &str) -> Result<Program, String> {
-    pandalang_parser::parse(source).map_err(|err| err.to_string())
+    pandalang_parser::parse(source)
+        .map_err(|errs| render_all(errs.iter().map(|err| err.to_diagnostic()), source))
 }
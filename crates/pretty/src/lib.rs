@@ -1,33 +1,159 @@
-use pandalang_parser::ast::expr::{App, BinOp, Bool, Expr, Fun, If, Int, Let, Str, Var};
+use pandalang_parser::{
+    ast::{
+        expr::{
+            Access, App, Arm, Ascribe, BinOp, Bool, Cons, Constructor, Expr, Field, Float, Fun, If,
+            Int, Let, List, Match, Not, Record, Str, Tuple, Update, Var,
+        },
+        pattern::{self, Pattern},
+        types::{self, Type},
+    },
+    escape::escape,
+};
 
 #[allow(unused)] // TODO: actually implement a pretty printer and expose it
 pub fn pretty(e: Expr) -> String {
     match e {
-        Expr::Int(Int { n }) => n.to_string(),
-        Expr::Str(Str { s }) => format!("\"{}\"", s),
-        Expr::Unit => "()".to_string(),
-        Expr::Bool(Bool { b }) => b.to_string(),
-        Expr::Var(Var { name }) => name,
-        Expr::BinOp(BinOp { left, right, kind }) => {
-            format!("{} {} {}", pretty(*left), kind.to_string(), pretty(*right))
+        Expr::Int(Int { n, .. }) => n.to_string(),
+        Expr::Float(Float { x, .. }) => format!("{:?}", x),
+        Expr::Str(Str { s, .. }) => format!("\"{}\"", escape(&s)),
+        Expr::Unit(_) => "()".to_string(),
+        Expr::Bool(Bool { b, .. }) => b.to_string(),
+        Expr::Var(Var { name, .. }) | Expr::Constructor(Constructor { name, .. }) => name,
+        Expr::Tuple(Tuple { elems, .. }) => {
+            let elems: Vec<_> = elems.into_iter().map(pretty).collect();
+            format!("({})", elems.join(", "))
         }
+        Expr::List(List { elems, .. }) => {
+            let elems: Vec<_> = elems.into_iter().map(pretty).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        Expr::Cons(Cons { head, tail, .. }) => {
+            format!("({}) :: {}", pretty(*head), pretty(*tail))
+        }
+        Expr::Record(Record { fields, .. }) => format!("{{ {} }}", pretty_fields(fields)),
+        Expr::Access(Access { expr, field, .. }) => format!("({}).{}", pretty(*expr), field),
+        Expr::Update(Update { expr, fields, .. }) => {
+            format!("{{ {} with {} }}", pretty(*expr), pretty_fields(fields))
+        }
+        Expr::BinOp(BinOp {
+            left, right, kind, ..
+        }) => {
+            format!("{} {} {}", pretty(*left), kind, pretty(*right))
+        }
+        Expr::Not(Not { expr, .. }) => format!("not ({})", pretty(*expr)),
         Expr::Let(Let {
             name,
             value,
             body,
             rec,
+            ..
         }) => {
             format!("let {} = {} in {}", name, pretty(*value), pretty(*body))
         }
-        Expr::Fun(Fun { arg, body }) => {
-            format!("fun {} -> {}", arg, pretty(*body))
-        }
-        Expr::App(App { fun, arg }) => format!("({}) ({})", pretty(*fun), pretty(*arg)),
-        Expr::If(If { check, then, els }) => format!(
+        Expr::Fun(Fun {
+            arg, arg_typ, body, ..
+        }) => match arg_typ {
+            Some(typ) => format!("fun ({} : {}) -> {}", arg, pretty_type(typ), pretty(*body)),
+            None => format!("fun {} -> {}", arg, pretty(*body)),
+        },
+        Expr::App(App { fun, arg, .. }) => format!("({}) ({})", pretty(*fun), pretty(*arg)),
+        Expr::If(If {
+            check, then, els, ..
+        }) => format!(
             "if {} then {} else {}",
             pretty(*check),
             pretty(*then),
             pretty(*els)
         ),
+        Expr::Match(Match { expr, arms, .. }) => {
+            let arms: Vec<_> = arms
+                .into_iter()
+                .map(|Arm { pattern, body, .. }| {
+                    format!("| {} -> ({})", pretty_pattern(pattern), pretty(*body))
+                })
+                .collect();
+            format!("match {} with {}", pretty(*expr), arms.join(" "))
+        }
+        Expr::Ascribe(Ascribe { expr, typ, .. }) => {
+            format!("({} : {})", pretty(*expr), pretty_type(typ))
+        }
+    }
+}
+
+fn pretty_fields(fields: Vec<Field>) -> String {
+    let fields: Vec<_> = fields
+        .into_iter()
+        .map(|Field { name, value, .. }| format!("{} = {}", name, pretty(value)))
+        .collect();
+    fields.join("; ")
+}
+
+fn pretty_pattern(p: Pattern) -> String {
+    match p {
+        Pattern::Wildcard(_) => "_".to_string(),
+        Pattern::Var(pattern::Var { name, .. }) => name,
+        Pattern::Int(pattern::Int { n, .. }) => n.to_string(),
+        Pattern::Str(pattern::Str { s, .. }) => format!("\"{}\"", escape(&s)),
+        Pattern::Unit(_) => "()".to_string(),
+        Pattern::Bool(pattern::Bool { b, .. }) => b.to_string(),
+        Pattern::Constructor(pattern::Constructor { name, args, .. }) => {
+            let args: Vec<_> = args
+                .into_iter()
+                .map(|arg| format!(" ({})", pretty_pattern(arg)))
+                .collect();
+            format!("{}{}", name, args.concat())
+        }
+        Pattern::Record(pattern::Record { fields, .. }) => {
+            let fields: Vec<_> = fields
+                .into_iter()
+                .map(|field| format!("{} = {}", field.name, pretty_pattern(field.pattern)))
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        Pattern::Tuple(pattern::Tuple { elems, .. }) => {
+            let elems: Vec<_> = elems.into_iter().map(pretty_pattern).collect();
+            format!("({})", elems.join(", "))
+        }
+        Pattern::List(pattern::List { elems, .. }) => {
+            let elems: Vec<_> = elems.into_iter().map(pretty_pattern).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        Pattern::Cons(pattern::Cons { head, tail, .. }) => {
+            format!("({}) :: {}", pretty_pattern(*head), pretty_pattern(*tail))
+        }
+    }
+}
+
+fn pretty_type(t: Type) -> String {
+    match t {
+        Type::Simple(types::Simple { name, .. }) => name,
+        Type::Var(types::Var { name, .. }) => format!("'{}", name),
+        Type::App(types::App { name, args, .. }) => {
+            let args: Vec<_> = args
+                .into_iter()
+                .map(|arg| format!("({})", pretty_type(arg)))
+                .collect();
+            format!("{} {}", name, args.join(" "))
+        }
+        Type::Tuple(types::Tuple { elems, .. }) => {
+            let elems: Vec<_> = elems
+                .into_iter()
+                .map(|elem| format!("({})", pretty_type(elem)))
+                .collect();
+            elems.join(" * ")
+        }
+        Type::Record(types::Record { fields, rest, .. }) => {
+            let fields: Vec<_> = fields
+                .into_iter()
+                .map(|field| format!("{} : {}", field.name, pretty_type(field.typ)))
+                .collect();
+            match rest {
+                Some(rest) => format!("{{ {} | '{} }}", fields.join("; "), rest.name),
+                None => format!("{{ {} }}", fields.join("; ")),
+            }
+        }
+        Type::Fun(types::Fun { from, to, .. }) => {
+            format!("({}) -> {}", pretty_type(*from), pretty_type(*to))
+        }
     }
 }
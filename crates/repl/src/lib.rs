@@ -87,8 +87,11 @@ fn eval_command() -> ReplCommand {
         execute: |source| {
             let mut stdout = std::io::stdout();
             let env = Evaluator::new(&mut stdout);
-            let ast = *pandalang_parser::parse_expr(source).map_err(|err| err.to_string())?;
-            let value_string = eval(env, ast).unwrap().to_string();
+            let ast = *pandalang_parser::parse_expr(source)
+                .map_err(|err| err.to_diagnostic().render(source))?;
+            let value_string = eval(env, ast)
+                .map_err(|err| err.to_diagnostic().render(source))?
+                .to_string();
             Ok(value_string)
         },
     }
@@ -97,9 +100,10 @@ fn eval_command() -> ReplCommand {
 fn type_check_command() -> ReplCommand {
     ReplCommand {
         execute: |source| {
-            let ast = pandalang_parser::parse_expr(source).map_err(|err| err.to_string())?;
-            let type_string =
-                pandalang_types::check_expr_to_string(*ast).map_err(|err| err.to_string())?;
+            let ast = pandalang_parser::parse_expr(source)
+                .map_err(|err| err.to_diagnostic().render(source))?;
+            let type_string = pandalang_types::check_expr_to_string(*ast)
+                .map_err(|err| err.to_diagnostic().render(source))?;
             Ok(type_string)
         },
     }
@@ -3,9 +3,14 @@ extern crate glob;
 use clap::Parser;
 use glob::glob;
 use libtest_mimic::{Failed, Trial};
-use pandalang_eval::Value;
+use pandalang_eval::{Evaluator, Registry, Value};
+use pandalang_parser::diagnostic::render_all;
+use pandalang_types::Globals;
 use similar_asserts::SimpleDiff;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 #[derive(Parser, Debug, Clone, Default)]
 struct MyArguments {
@@ -26,10 +31,18 @@ fn get_tests(record: bool) -> Vec<Trial> {
     let parse_tests = get_parse_tests(record);
     let type_check_tests = get_type_check_tests(record);
     let eval_tests = get_eval_tests(record);
+    let diagnostic_tests = get_diagnostic_tests(record);
+    let module_tests = get_module_tests(record);
+    let host_tests = get_host_tests(record);
+    let signature_tests = get_signature_tests(record);
 
     parse_tests
         .chain(type_check_tests)
         .chain(eval_tests)
+        .chain(diagnostic_tests)
+        .chain(module_tests)
+        .chain(host_tests)
+        .chain(signature_tests)
         .collect()
 }
 
@@ -42,8 +55,8 @@ struct ProgramOutput {
 
 fn get_eval_tests(record: bool) -> impl Iterator<Item = Trial> {
     get_input_sources("inputs/eval/**/*.panda").map(snapshot_trial(record, |src| {
-        let program = pandalang_parser::parse(&src).map_err(|err| err.to_string())?;
-        pandalang_types::check_prog_to_strings(program.clone()).map_err(|err| err.to_string())?;
+        let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+        pandalang_types::check_executable(program.clone()).map_err(|errs| format!("{:?}", errs))?;
         let mut stdout = Vec::new();
         let result =
             pandalang_eval::run_program(program, &mut stdout).map(|main_return| ProgramOutput {
@@ -85,7 +98,7 @@ fn get_type_check_tests(record: bool) -> impl Iterator<Item = Trial> {
     let prog_trials = get_input_sources("inputs/type_check/progs/**/*.panda").map(snapshot_trial(
         record,
         |src| {
-            let program = pandalang_parser::parse(&src).map_err(|err| err.to_string())?;
+            let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
             Ok(format!(
                 "{:#?}",
                 pandalang_types::check_prog_to_strings(program)
@@ -93,7 +106,197 @@ fn get_type_check_tests(record: bool) -> impl Iterator<Item = Trial> {
         },
     ));
 
-    expr_trials.chain(prog_trials)
+    let scheme_trials = get_input_sources("inputs/type_check/schemes/**/*.panda").map(
+        snapshot_trial(record, |src| {
+            let ast = *pandalang_parser::parse_expr(&src).map_err(|err| err.to_string())?;
+            Ok(format!("{:#?}", pandalang_types::check_expr(ast)))
+        }),
+    );
+
+    let typed_trials = get_input_sources("inputs/type_check/typed/**/*.panda").map(snapshot_trial(
+        record,
+        |src| {
+            let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+            Ok(format!(
+                "{:#?}",
+                pandalang_types::check_program(program).map(|checked| checked.program)
+            ))
+        },
+    ));
+
+    expr_trials
+        .chain(prog_trials)
+        .chain(scheme_trials)
+        .chain(typed_trials)
+}
+
+fn get_diagnostic_tests(record: bool) -> impl Iterator<Item = Trial> {
+    let parse_trials = get_input_sources("inputs/diagnostics/parse/**/*.panda").map(
+        snapshot_trial(record, |src| match pandalang_parser::parse(&src) {
+            Ok(_) => Err("Expected a parse error".to_string()),
+            Err(errs) => Ok(render_all(errs.iter().map(|err| err.to_diagnostic()), &src)),
+        }),
+    );
+
+    let type_check_trials = get_input_sources("inputs/diagnostics/type_check/**/*.panda").map(
+        snapshot_trial(record, |src| {
+            let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+            match pandalang_types::check_prog_to_strings(program) {
+                Ok(_) => Err("Expected a type error".to_string()),
+                Err(errs) => Ok(render_all(errs.iter().map(|err| err.to_diagnostic()), &src)),
+            }
+        }),
+    );
+
+    let warning_trials = get_input_sources("inputs/diagnostics/warnings/**/*.panda").map(
+        snapshot_trial(record, |src| {
+            let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+            let checked =
+                pandalang_types::check_program(program).map_err(|errs| format!("{:?}", errs))?;
+            if checked.warnings.is_empty() {
+                return Err("Expected a warning".to_string());
+            }
+            Ok(render_all(
+                checked
+                    .warnings
+                    .iter()
+                    .map(|warning| warning.to_diagnostic()),
+                &src,
+            ))
+        }),
+    );
+
+    let main_trials = get_input_sources("inputs/diagnostics/main/**/*.panda").map(snapshot_trial(
+        record,
+        |src| {
+            let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+            match pandalang_types::check_executable(program) {
+                Ok(_) => Err("Expected main to be rejected".to_string()),
+                Err(errs) => Ok(render_all(errs.iter().map(|err| err.to_diagnostic()), &src)),
+            }
+        },
+    ));
+
+    let eval_trials = get_input_sources("inputs/diagnostics/eval/**/*.panda").map(snapshot_trial(
+        record,
+        |src| {
+            let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+            pandalang_types::check_executable(program.clone())
+                .map_err(|errs| format!("{:?}", errs))?;
+            match pandalang_eval::run_program(program, &mut Vec::new()) {
+                Ok(_) => Err("Expected a runtime error".to_string()),
+                Err(err) => Ok(err.to_diagnostic().render(&src)),
+            }
+        },
+    ));
+
+    parse_trials
+        .chain(type_check_trials)
+        .chain(warning_trials)
+        .chain(main_trials)
+        .chain(eval_trials)
+}
+
+// Each test is a directory of modules, run from its `main.panda`
+fn get_module_tests(record: bool) -> impl Iterator<Item = Trial> {
+    get_input_sources("inputs/modules/**/main.panda").map(move |InputSource { path, .. }| {
+        Trial::test(path.clone(), move || {
+            let graph = match pandalang_parser::module::load(Path::new(&path)) {
+                Ok(graph) => graph,
+                Err(err) => return check_snapshot(record, &path, err.render()),
+            };
+            if let Err(errs) = pandalang_types::check_modules(graph.clone(), &Globals::default()) {
+                let diagnostics = errs
+                    .iter()
+                    .map(|err| (err.module, err.error.to_diagnostic()));
+                return check_snapshot(record, &path, graph.render_all(diagnostics));
+            }
+            let mut stdout = Vec::new();
+            let evaluator = Evaluator::new(&mut stdout);
+            let actual = match pandalang_eval::run_modules(evaluator, graph.clone()) {
+                Ok(main_return) => format!(
+                    "{:#?}",
+                    ProgramOutput {
+                        main_return,
+                        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+                    }
+                ),
+                Err(err) => graph.render_all([(err.module, err.error.to_diagnostic())]),
+            };
+            check_snapshot(record, &path, actual)
+        })
+    })
+}
+
+// Programs run with builtins registered by the host, as an embedder would
+fn get_host_tests(record: bool) -> impl Iterator<Item = Trial> {
+    get_input_sources("inputs/host/**/*.panda").map(snapshot_trial(record, |src| {
+        let registry = host_registry();
+        let program = pandalang_parser::parse(&src).map_err(|errs| format!("{:?}", errs))?;
+        let globals = Globals {
+            builtins: registry.signatures(),
+            ..Globals::default()
+        };
+        if let Err(errs) = pandalang_types::check_executable_with(program.clone(), &globals) {
+            return Ok(render_all(errs.iter().map(|err| err.to_diagnostic()), &src));
+        }
+        let mut stdout = Vec::new();
+        let evaluator = Evaluator::new(&mut stdout).with_registry(registry);
+        let result = pandalang_eval::run_program_with(evaluator, program);
+        match result {
+            Ok(main_return) => Ok(format!(
+                "{:#?}",
+                ProgramOutput {
+                    main_return,
+                    stdout: String::from_utf8_lossy(&stdout).into_owned(),
+                }
+            )),
+            Err(err) => Ok(err.to_diagnostic().render(&src)),
+        }
+    }))
+}
+
+// Signatures the host gives a builtin, each of which should be rejected when it's registered
+fn get_signature_tests(record: bool) -> impl Iterator<Item = Trial> {
+    get_input_sources("inputs/signatures/**/*.panda").map(snapshot_trial(record, |src| {
+        let result = Registry::new().register("native", src.trim_end(), |_| Ok(Value::Unit));
+        Ok(match result {
+            Ok(()) => "Registered".to_string(),
+            Err(err) => err.to_string(),
+        })
+    }))
+}
+
+fn host_registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .register("shout", "Str -> Str", |args| match args {
+            [Value::Str(s)] => Ok(Value::Str(format!("{}!", s.to_uppercase()))),
+            _ => Err("Not a Str".to_string()),
+        })
+        .unwrap();
+    registry
+        .register("clamp", "Int -> Int -> Int -> Int", |args| match args {
+            [Value::Int(lo), Value::Int(hi), Value::Int(n)] if lo <= hi => {
+                Ok(Value::Int(*n.max(lo).min(hi)))
+            }
+            [Value::Int(_), Value::Int(_), Value::Int(_)] => {
+                Err("The lower bound is above the upper bound".to_string())
+            }
+            _ => Err("Not an Int".to_string()),
+        })
+        .unwrap();
+    registry
+        .register("answer", "Int", |_| Ok(Value::Int(42)))
+        .unwrap();
+    registry
+        .register("show", "'a -> Str", |args| match args {
+            [Value::Str(s)] => Ok(Value::Str(format!("{:?}", s))),
+            [value] => Ok(Value::Str(value.to_string())),
+            _ => Err("Expected one argument".to_string()),
+        })
+        .unwrap();
+    registry
 }
 
 struct InputSource {
@@ -119,21 +322,26 @@ fn snapshot_trial(
     move |InputSource { path, src }| {
         Trial::test(path.clone(), move || {
             let actual = get_actual(src).map_err(Failed::from)?;
-            let expected_path: PathBuf = format!("{}.expected", path).into();
-            if record {
-                fs::write(expected_path, actual).unwrap();
-                Ok(())
-            } else if expected_path.exists() {
-                let expected = fs::read_to_string(expected_path).unwrap();
-                if expected == actual {
-                    Ok(())
-                } else {
-                    let diff = SimpleDiff::from_str(&expected, &actual, "expected", "actual");
-                    Err(diff.into())
-                }
-            } else {
-                Err("Couldn't find .expected file. Did you mean to --record it?".into())
-            }
+            check_snapshot(record, &path, actual)
         })
     }
 }
+
+/// Compares `actual` with the `.expected` file of the input at `path`, or records it
+fn check_snapshot(record: bool, path: &str, actual: String) -> Result<(), Failed> {
+    let expected_path: PathBuf = format!("{}.expected", path).into();
+    if record {
+        fs::write(expected_path, actual).unwrap();
+        Ok(())
+    } else if expected_path.exists() {
+        let expected = fs::read_to_string(expected_path).unwrap();
+        if expected == actual {
+            Ok(())
+        } else {
+            let diff = SimpleDiff::from_str(&expected, &actual, "expected", "actual");
+            Err(diff.into())
+        }
+    } else {
+        Err("Couldn't find .expected file. Did you mean to --record it?".into())
+    }
+}
@@ -1,19 +1,58 @@
 // An implementation of Algorithm J for Hindley-Milner type checking
 // Based in part on this implementation: https://github.com/jfecher/algorithm-j/blob/7119150ae1822deac1dfe1dbb14f172d7c75e921/j.ml
 
-use std::{cmp::min, collections::HashMap};
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+};
 
-use pandalang_parser::ast::expr::*;
+use pandalang_parser::{
+    ast::{
+        self,
+        expr::*,
+        pattern::{self, Pattern},
+        stmt::{ConstructorDef, TypeDef},
+    },
+    prelude,
+    span::Span,
+};
 
 use super::{
-    error::Error, monomorphize::monomorphize, polymorphize::polymorphize, tvars::TVars, Level,
-    Polytype, TVar, TVarRef, Type,
+    checker_type_of_ast_type,
+    concretize::Concretizer,
+    error::{Error, Reason, Warning},
+    exhaustive,
+    monomorphize::monomorphize,
+    polymorphize::polymorphize,
+    tvars::TVars,
+    typed::{self, ExprKind, PatternKind},
+    Constructor, DataType, Level, Polytype, TVar, TVarRef, Type,
 };
 
+/// Why two types failed to unify. The caller knows which expression was being checked, so it
+/// turns this into an [Error] describing the whole types rather than just the parts that differ.
+enum UnifyError {
+    Mismatch,
+    Occurs(Type, Type),
+}
+
 pub(super) struct Checker {
     pub cur_level: Level,
     pub tvars: TVars,
     pub bindings: HashMap<String, Polytype>,
+    pub types: HashMap<String, DataType>,
+    pub constructors: HashMap<String, Constructor>,
+    /// The type variables named in annotations, which are shared by every annotation in the same
+    /// top-level statement
+    pub annotation_vars: HashMap<String, Type>,
+    pub warnings: Vec<Warning>,
+    /// The name of the module being checked, if it's imported by another. The types and
+    /// constructors it defines are qualified by it, so they can't clash with other modules'.
+    pub module: Option<String>,
+    /// The modules the module being checked imports
+    pub imports: HashSet<String>,
+    /// The top-level bindings of each module checked so far, by module name
+    pub modules: HashMap<String, HashMap<String, Polytype>>,
 }
 
 impl Checker {
@@ -22,107 +61,583 @@ impl Checker {
             cur_level: Level(0),
             tvars: TVars::new(),
             bindings: HashMap::new(),
+            types: HashMap::new(),
+            constructors: HashMap::new(),
+            annotation_vars: HashMap::new(),
+            warnings: Vec::new(),
+            module: None,
+            imports: HashSet::new(),
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Qualifies the name of a type or constructor defined by the module being checked
+    pub fn qualify(&self, name: &str) -> String {
+        match &self.module {
+            Some(module) => format!("{}.{}", module, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The name a type or constructor is known by in every module, given the names `defined` so
+    /// far. A name that is already qualified must be qualified by an imported module. One that
+    /// isn't is defined by the module being checked, or else by the prelude.
+    pub fn resolve<T>(
+        &self,
+        name: &str,
+        span: Span,
+        defined: &HashMap<String, T>,
+    ) -> Result<String, Error> {
+        match name.split_once('.') {
+            Some((module, _)) if !self.imports.contains(module) => Err(Error::ModuleNotImported {
+                module: module.to_string(),
+                span,
+            }),
+            Some(_) => Ok(name.to_string()),
+            None => {
+                let qualified = self.qualify(name);
+                let prelude = format!("{}.{}", prelude::MODULE, name);
+                if !defined.contains_key(&qualified) && defined.contains_key(&prelude) {
+                    Ok(prelude)
+                } else {
+                    Ok(qualified)
+                }
+            }
+        }
+    }
+
+    /// The name to report a type or constructor by in the module being checked, given the names
+    /// `defined` so far. The prelude's are reported by the name they're written as, unless the
+    /// module defines its own by that name.
+    pub fn display_name<T>(&self, name: &str, defined: &HashMap<String, T>) -> String {
+        let unqualified = prelude::unqualify(name);
+        if unqualified != name && !defined.contains_key(&self.qualify(unqualified)) {
+            unqualified.to_string()
+        } else {
+            name.to_string()
         }
     }
 
-    pub fn check(&mut self, expr: Expr) -> Result<Type, Error> {
-        match expr {
-            Expr::Int(_) => Ok(Type::Int),
-            Expr::Str(_) => Ok(Type::Str),
-            Expr::Unit => Ok(Type::Unit),
-            Expr::Bool(_) => Ok(Type::Bool),
-            Expr::Var(Var { name }) => match self.bindings.get(&name) {
-                Some(t) => Ok(monomorphize(self, t.clone())),
-                None => Err(Error::NotInScope { name }),
+    /// Infers the type of `expr`, returning it annotated with the type of each of its
+    /// subexpressions
+    pub fn check(&mut self, expr: Expr) -> Result<typed::Expr<Type>, Error> {
+        let span = expr.span();
+        let (kind, ty) = match expr {
+            Expr::Int(Int { n, .. }) => (ExprKind::Int(n), Type::Int),
+            Expr::Float(Float { x, .. }) => (ExprKind::Float(x), Type::Float),
+            Expr::Str(Str { s, .. }) => (ExprKind::Str(s), Type::Str),
+            Expr::Unit(_) => (ExprKind::Unit, Type::Unit),
+            Expr::Bool(Bool { b, .. }) => (ExprKind::Bool(b), Type::Bool),
+            Expr::Var(Var { name, span }) => match self.bindings.get(&name) {
+                Some(t) => {
+                    let t = monomorphize(self, t.clone());
+                    (ExprKind::Var(name), t)
+                }
+                None => {
+                    self.resolve(&name, span, &self.bindings)?;
+                    return Err(Error::NotInScope { name, span });
+                }
             },
-            Expr::App(App { fun, arg }) => {
-                let fun_t = self.check(*fun)?;
-                let arg_t = self.check(*arg)?;
+            Expr::Constructor(ast::expr::Constructor { name, span }) => {
+                match self
+                    .constructors
+                    .get(&self.resolve(&name, span, &self.constructors)?)
+                {
+                    Some(constructor) => {
+                        let t = monomorphize(self, constructor.poly.clone());
+                        (ExprKind::Constructor(name), t)
+                    }
+                    None => return Err(Error::UnknownConstructor { name, span }),
+                }
+            }
+            Expr::Tuple(Tuple { elems, .. }) => {
+                let elems: Vec<_> = elems
+                    .into_iter()
+                    .map(|elem| self.check(elem))
+                    .collect::<Result<_, _>>()?;
+                let t = Type::Tuple(elems.iter().map(|elem| elem.ty.clone()).collect());
+                (ExprKind::Tuple(elems), t)
+            }
+            Expr::List(List { elems, .. }) => {
+                let elem_t = self.new_tvar();
+                let elems: Vec<_> = elems
+                    .into_iter()
+                    .map(|elem| {
+                        let elem = self.check(elem)?;
+                        self.expect(
+                            elem_t.clone(),
+                            elem.ty.clone(),
+                            elem.span,
+                            Reason::ListElems,
+                        )?;
+                        Ok(elem)
+                    })
+                    .collect::<Result<_, _>>()?;
+                (ExprKind::List(elems), Type::List(Box::new(elem_t)))
+            }
+            Expr::Cons(Cons { head, tail, .. }) => {
+                let head = self.check(*head)?;
+                let tail = self.check(*tail)?;
+                let t = Type::List(Box::new(head.ty.clone()));
+                self.expect(t.clone(), tail.ty.clone(), tail.span, Reason::Cons)?;
+                let kind = ExprKind::Cons {
+                    head: Box::new(head),
+                    tail: Box::new(tail),
+                };
+                (kind, t)
+            }
+            Expr::Record(Record { fields, .. }) => {
+                check_distinct_fields(fields.iter().map(|field| (&field.name, field.span)))?;
+                let fields = self.check_fields(fields)?;
+                let field_ts = fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.ty.clone()));
+                let t = Type::Record(Box::new(row(field_ts, Type::RowEmpty)));
+                (ExprKind::Record(fields), t)
+            }
+            Expr::Access(Access { expr, field, .. }) => {
+                let expr = self.check(*expr)?;
+                let field_t = self.new_tvar();
+                let rest = self.new_tvar();
+                let record_t =
+                    Type::Record(Box::new(row([(field.clone(), field_t.clone())], rest)));
+                self.expect(
+                    record_t,
+                    expr.ty.clone(),
+                    expr.span,
+                    Reason::Field {
+                        name: field.clone(),
+                    },
+                )?;
+                let kind = ExprKind::Access {
+                    expr: Box::new(expr),
+                    field,
+                };
+                (kind, field_t)
+            }
+            Expr::Update(Update { expr, fields, .. }) => {
+                let expr = self.check(*expr)?;
+                check_distinct_fields(fields.iter().map(|field| (&field.name, field.span)))?;
+                let fields = self.check_fields(fields)?;
+                // The record must already have each field, with the type of its new value
+                let field_ts = fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.ty.clone()));
+                let rest = self.new_tvar();
+                let record_t = Type::Record(Box::new(row(field_ts, rest)));
+                self.expect(record_t, expr.ty.clone(), expr.span, Reason::Update)?;
+                let t = expr.ty.clone();
+                let kind = ExprKind::Update {
+                    expr: Box::new(expr),
+                    fields,
+                };
+                (kind, t)
+            }
+            Expr::App(App { fun, arg, .. }) => {
+                let fun = self.check(*fun)?;
+                let arg = self.check(*arg)?;
+                let param_t = self.new_tvar();
                 let t = self.new_tvar();
-                self.unify(fun_t, Type::Fun(Box::new(arg_t), Box::new(t.clone())))?;
-                Ok(t)
+                self.expect(
+                    Type::Fun(Box::new(param_t.clone()), Box::new(t.clone())),
+                    fun.ty.clone(),
+                    fun.span,
+                    Reason::Applied,
+                )?;
+                self.expect(param_t, arg.ty.clone(), arg.span, Reason::Argument)?;
+                let kind = ExprKind::App {
+                    fun: Box::new(fun),
+                    arg: Box::new(arg),
+                };
+                (kind, t)
             }
-            Expr::Fun(Fun { arg, body }) => {
-                let in_t = self.new_tvar();
-                self.bindings
+            Expr::Fun(Fun {
+                arg, arg_typ, body, ..
+            }) => {
+                let in_t = match arg_typ {
+                    Some(typ) => self.annotation_type(typ)?,
+                    None => self.new_tvar(),
+                };
+                let shadowed = self
+                    .bindings
                     .insert(arg.clone(), Polytype(vec![], in_t.clone()));
-                let out_t = self.check(*body)?;
-                self.bindings.remove(&arg);
-                Ok(Type::Fun(Box::new(in_t), Box::new(out_t)))
+                let body = self.check(*body)?;
+                self.restore_binding(&arg, shadowed);
+                let t = Type::Fun(Box::new(in_t), Box::new(body.ty.clone()));
+                let kind = ExprKind::Fun {
+                    arg,
+                    body: Box::new(body),
+                };
+                (kind, t)
             }
             Expr::Let(Let {
                 name,
                 value,
                 body,
                 rec,
+                ..
             }) => {
-                self.check_let_value(name.clone(), *value, rec)?;
-                let t = self.check(*body)?;
-                self.bindings.remove(&name);
-                Ok(t)
+                let shadowed = self.bindings.get(&name).cloned();
+                let value = self.check_let_value(name.clone(), *value, rec)?;
+                let body = self.check(*body)?;
+                self.restore_binding(&name, shadowed);
+                let t = body.ty.clone();
+                let kind = ExprKind::Let {
+                    name,
+                    value: Box::new(value),
+                    body: Box::new(body),
+                    rec,
+                };
+                (kind, t)
             }
             // TODO: everything after this point can be desugared to the rules above. We should do that to make the type checker simpler
-            Expr::BinOp(BinOp { left, right, kind }) => {
-                let op_t = match kind {
+            Expr::BinOp(BinOp {
+                left, right, kind, ..
+            }) => {
+                let left = self.check(*left)?;
+                let right = self.check(*right)?;
+                let reason = Reason::Operand {
+                    op: kind.to_string(),
+                };
+                let t = match kind {
                     BinOpKind::Add
                     | BinOpKind::Sub
                     | BinOpKind::Mul
                     | BinOpKind::Div
-                    | BinOpKind::Rem => Type::Fun(
-                        Box::new(Type::Int),
-                        Box::new(Type::Fun(Box::new(Type::Int), Box::new(Type::Int))),
-                    ),
-                    BinOpKind::Eql => {
-                        let t = self.new_tvar();
-                        Type::Fun(
-                            Box::new(t.clone()),
-                            Box::new(Type::Fun(Box::new(t), Box::new(Type::Bool))),
-                        )
+                    | BinOpKind::Rem => {
+                        self.expect(Type::Int, left.ty.clone(), left.span, reason.clone())?;
+                        self.expect(Type::Int, right.ty.clone(), right.span, reason)?;
+                        Type::Int
+                    }
+                    BinOpKind::Lt | BinOpKind::Gt | BinOpKind::Le | BinOpKind::Ge => {
+                        self.expect(Type::Int, left.ty.clone(), left.span, reason.clone())?;
+                        self.expect(Type::Int, right.ty.clone(), right.span, reason)?;
+                        Type::Bool
+                    }
+                    BinOpKind::FAdd | BinOpKind::FSub | BinOpKind::FMul | BinOpKind::FDiv => {
+                        self.expect(Type::Float, left.ty.clone(), left.span, reason.clone())?;
+                        self.expect(Type::Float, right.ty.clone(), right.span, reason)?;
+                        Type::Float
+                    }
+                    BinOpKind::FLt | BinOpKind::FGt | BinOpKind::FLe | BinOpKind::FGe => {
+                        self.expect(Type::Float, left.ty.clone(), left.span, reason.clone())?;
+                        self.expect(Type::Float, right.ty.clone(), right.span, reason)?;
+                        Type::Bool
+                    }
+                    BinOpKind::Concat => {
+                        self.expect(Type::Str, left.ty.clone(), left.span, reason.clone())?;
+                        self.expect(Type::Str, right.ty.clone(), right.span, reason)?;
+                        Type::Str
+                    }
+                    BinOpKind::And | BinOpKind::Or => {
+                        self.expect(Type::Bool, left.ty.clone(), left.span, reason.clone())?;
+                        self.expect(Type::Bool, right.ty.clone(), right.span, reason)?;
+                        Type::Bool
+                    }
+                    BinOpKind::Eql | BinOpKind::Neq => {
+                        self.expect(
+                            left.ty.clone(),
+                            right.ty.clone(),
+                            right.span,
+                            Reason::Equality {
+                                op: kind.to_string(),
+                            },
+                        )?;
+                        Type::Bool
                     }
                 };
-                let left_t = self.check(*left)?;
-                let right_t = self.check(*right)?;
-                let t = self.new_tvar();
-                self.unify(
-                    op_t,
-                    Type::Fun(
-                        Box::new(left_t),
-                        Box::new(Type::Fun(Box::new(right_t), Box::new(t.clone()))),
-                    ),
+                let kind = ExprKind::BinOp {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    kind,
+                };
+                (kind, t)
+            }
+            Expr::Not(Not { expr, .. }) => {
+                let expr = self.check(*expr)?;
+                self.expect(Type::Bool, expr.ty.clone(), expr.span, Reason::Not)?;
+                let kind = ExprKind::Not {
+                    expr: Box::new(expr),
+                };
+                (kind, Type::Bool)
+            }
+            Expr::If(If {
+                check, then, els, ..
+            }) => {
+                let check = self.check(*check)?;
+                self.expect(
+                    Type::Bool,
+                    check.ty.clone(),
+                    check.span,
+                    Reason::IfCondition,
+                )?;
+                let then = self.check(*then)?;
+                let els = self.check(*els)?;
+                self.expect(
+                    then.ty.clone(),
+                    els.ty.clone(),
+                    els.span,
+                    Reason::IfBranches,
                 )?;
-                Ok(t)
+                let t = then.ty.clone();
+                let kind = ExprKind::If {
+                    check: Box::new(check),
+                    then: Box::new(then),
+                    els: Box::new(els),
+                };
+                (kind, t)
+            }
+            Expr::Match(Match { expr, arms, .. }) => {
+                let expr = self.check(*expr)?;
+                let t = self.new_tvar();
+                let arms: Vec<_> = arms
+                    .into_iter()
+                    .map(|arm| self.check_arm(arm, &expr.ty, &t))
+                    .collect::<Result<_, _>>()?;
+                let patterns = arms.iter().map(|arm| &arm.pattern);
+                let warnings = exhaustive::check_match(self, patterns, expr.span);
+                self.warnings.extend(warnings);
+                let kind = ExprKind::Match {
+                    expr: Box::new(expr),
+                    arms,
+                };
+                (kind, t)
             }
-            Expr::If(If { check, then, els }) => {
-                let check_t = self.check(*check)?;
-                self.unify(check_t, Type::Bool)?;
-                let then_t = self.check(*then)?;
-                let els_t = self.check(*els)?;
-                self.unify(then_t.clone(), els_t)?;
-                Ok(then_t)
+            Expr::Ascribe(Ascribe { expr, typ, .. }) => {
+                let typ_span = typ.span();
+                let t = self.annotation_type(typ)?;
+                let expr = self.check(*expr)?;
+                self.expect(t.clone(), expr.ty.clone(), typ_span, Reason::Annotation)?;
+                let kind = ExprKind::Ascribe {
+                    expr: Box::new(expr),
+                };
+                (kind, t)
             }
+        };
+        Ok(typed::Expr { kind, ty, span })
+    }
+
+    fn check_fields(
+        &mut self,
+        fields: Vec<Field>,
+    ) -> Result<Vec<(String, typed::Expr<Type>)>, Error> {
+        fields
+            .into_iter()
+            .map(|Field { name, value, .. }| Ok((name, self.check(value)?)))
+            .collect()
+    }
+
+    /// Checks an arm of a `match` on a value of type `expr_t`, whose arms all have type `t`
+    fn check_arm(&mut self, arm: Arm, expr_t: &Type, t: &Type) -> Result<typed::Arm<Type>, Error> {
+        let Arm {
+            pattern,
+            body,
+            span,
+        } = arm;
+        let mut bound = Vec::new();
+        let pattern = self.check_pattern(pattern, expr_t, &mut bound)?;
+
+        let shadowed: Vec<_> = bound
+            .into_iter()
+            .map(|(name, t)| {
+                let shadowed = self.bindings.insert(name.clone(), Polytype(vec![], t));
+                (name, shadowed)
+            })
+            .collect();
+        let body = self.check(*body)?;
+        for (name, shadowed) in shadowed {
+            self.restore_binding(&name, shadowed);
         }
+
+        self.expect(t.clone(), body.ty.clone(), body.span, Reason::MatchArms)?;
+        Ok(typed::Arm {
+            pattern,
+            body,
+            span,
+        })
     }
 
-    fn enter_level(&mut self) {
+    /// Checks that `pattern` matches values of type `expected`, adding the variables it binds to
+    /// `bound`
+    fn check_pattern(
+        &mut self,
+        pattern: Pattern,
+        expected: &Type,
+        bound: &mut Vec<(String, Type)>,
+    ) -> Result<typed::Pattern<Type>, Error> {
+        let span = pattern.span();
+        let kind = match pattern {
+            Pattern::Wildcard(_) => PatternKind::Wildcard,
+            Pattern::Var(pattern::Var { name, span }) => {
+                if bound.iter().any(|(bound, _)| *bound == name) {
+                    return Err(Error::DuplicatePatternVar { name, span });
+                }
+                bound.push((name.clone(), expected.clone()));
+                PatternKind::Var(name)
+            }
+            Pattern::Int(pattern::Int { n, .. }) => {
+                self.expect(expected.clone(), Type::Int, span, Reason::Pattern)?;
+                PatternKind::Int(n)
+            }
+            Pattern::Str(pattern::Str { s, .. }) => {
+                self.expect(expected.clone(), Type::Str, span, Reason::Pattern)?;
+                PatternKind::Str(s)
+            }
+            Pattern::Unit(_) => {
+                self.expect(expected.clone(), Type::Unit, span, Reason::Pattern)?;
+                PatternKind::Unit
+            }
+            Pattern::Bool(pattern::Bool { b, .. }) => {
+                self.expect(expected.clone(), Type::Bool, span, Reason::Pattern)?;
+                PatternKind::Bool(b)
+            }
+            Pattern::Tuple(pattern::Tuple { elems, span }) => {
+                let elem_ts: Vec<_> = elems.iter().map(|_| self.new_tvar()).collect();
+                self.expect(
+                    expected.clone(),
+                    Type::Tuple(elem_ts.clone()),
+                    span,
+                    Reason::Pattern,
+                )?;
+                let elems = elems
+                    .into_iter()
+                    .zip(elem_ts)
+                    .map(|(elem, elem_t)| self.check_pattern(elem, &elem_t, bound))
+                    .collect::<Result<_, _>>()?;
+                PatternKind::Tuple(elems)
+            }
+            Pattern::List(pattern::List { elems, span }) => {
+                let elem_t = self.new_tvar();
+                let list_t = Type::List(Box::new(elem_t.clone()));
+                self.expect(expected.clone(), list_t, span, Reason::Pattern)?;
+                let elems = elems
+                    .into_iter()
+                    .map(|elem| self.check_pattern(elem, &elem_t, bound))
+                    .collect::<Result<_, _>>()?;
+                PatternKind::List(elems)
+            }
+            Pattern::Cons(pattern::Cons { head, tail, span }) => {
+                let elem_t = self.new_tvar();
+                let list_t = Type::List(Box::new(elem_t.clone()));
+                self.expect(expected.clone(), list_t.clone(), span, Reason::Pattern)?;
+                let head = self.check_pattern(*head, &elem_t, bound)?;
+                let tail = self.check_pattern(*tail, &list_t, bound)?;
+                PatternKind::Cons {
+                    head: Box::new(head),
+                    tail: Box::new(tail),
+                }
+            }
+            Pattern::Record(pattern::Record { fields, span }) => {
+                check_distinct_fields(fields.iter().map(|field| (&field.name, field.span)))?;
+                let field_ts: Vec<_> = fields.iter().map(|_| self.new_tvar()).collect();
+                // The pattern only mentions some of the fields the record may have
+                let rest = self.new_tvar();
+                let names = fields.iter().map(|field| field.name.clone());
+                let record_t = Type::Record(Box::new(row(names.zip(field_ts.clone()), rest)));
+                self.expect(expected.clone(), record_t, span, Reason::Pattern)?;
+                let fields = fields
+                    .into_iter()
+                    .zip(field_ts)
+                    .map(|(field, field_t)| {
+                        let pattern = self.check_pattern(field.pattern, &field_t, bound)?;
+                        Ok((field.name, pattern))
+                    })
+                    .collect::<Result<_, _>>()?;
+                PatternKind::Record(fields)
+            }
+            Pattern::Constructor(pattern::Constructor { name, args, span }) => {
+                // Exhaustiveness checking looks the constructor up by the name every module
+                // knows it by, so that's the name the typed pattern has
+                let qualified = self.resolve(&name, span, &self.constructors)?;
+                let constructor = match self.constructors.get(&qualified) {
+                    Some(constructor) => constructor.clone(),
+                    None => return Err(Error::UnknownConstructor { name, span }),
+                };
+                if args.len() != constructor.arity {
+                    return Err(Error::ConstructorArity {
+                        name,
+                        expected: constructor.arity,
+                        found: args.len(),
+                        span,
+                    });
+                }
+                // Peel a parameter off the constructor's type for each field, leaving the type
+                // it constructs
+                let mut t = monomorphize(self, constructor.poly);
+                let mut field_ts = Vec::new();
+                for _ in &args {
+                    let (field_t, rest) = match t {
+                        Type::Fun(field_t, rest) => (*field_t, *rest),
+                        // A constructor whose fields failed to check has the error type
+                        _ => (Type::Error, Type::Error),
+                    };
+                    field_ts.push(field_t);
+                    t = rest;
+                }
+                self.expect(expected.clone(), t, span, Reason::Pattern)?;
+
+                let args = args
+                    .into_iter()
+                    .zip(field_ts)
+                    .map(|(arg, field_t)| self.check_pattern(arg, &field_t, bound))
+                    .collect::<Result<_, _>>()?;
+                PatternKind::Constructor {
+                    name: qualified,
+                    args,
+                }
+            }
+        };
+        Ok(typed::Pattern {
+            kind,
+            ty: expected.clone(),
+            span,
+        })
+    }
+
+    /// Undoes binding `name` in a nested scope, bringing back the binding it shadowed, if any
+    fn restore_binding(&mut self, name: &str, shadowed: Option<Polytype>) {
+        match shadowed {
+            Some(poly) => self.bindings.insert(name.to_string(), poly),
+            None => self.bindings.remove(name),
+        };
+    }
+
+    /// Translates the type in an annotation. Unlike in a `declare`, type variables aren't
+    /// quantified; they stand for a type to be inferred.
+    fn annotation_type(&mut self, typ: ast::types::Type) -> Result<Type, Error> {
+        let mut vars = std::mem::take(&mut self.annotation_vars);
+        let typ = checker_type_of_ast_type(self, typ, &mut vars, true);
+        self.annotation_vars = vars;
+        typ
+    }
+
+    pub fn enter_level(&mut self) {
         let Level(level) = self.cur_level;
         self.cur_level = Level(level + 1);
     }
 
-    fn exit_level(&mut self) {
+    pub fn exit_level(&mut self) {
         let Level(level) = self.cur_level;
         self.cur_level = Level(level - 1);
     }
 
     pub fn new_tvar(&mut self) -> Type {
-        Type::Var(
-            self.tvars
-                .add(|var_ref| TVar::Unbound(var_ref, self.cur_level)),
-        )
+        self.new_tvar_at(self.cur_level)
+    }
+
+    fn new_tvar_at(&mut self, level: Level) -> Type {
+        Type::Var(self.tvars.add(|var_ref| TVar::Unbound(var_ref, level)))
     }
 
     fn occurs(&mut self, id: TVarRef, level: Level, typ: Type) -> bool {
         match typ {
-            Type::Int | Type::Str | Type::Unit | Type::Bool => false,
+            Type::Int
+            | Type::Float
+            | Type::Str
+            | Type::Unit
+            | Type::Bool
+            | Type::RowEmpty
+            | Type::Error => false,
             Type::Var(tvar) => match self.tvars.get(tvar) {
                 TVar::Bound(t) => self.occurs(id, level, t.clone()),
                 TVar::Unbound(b_id, b_level) => {
@@ -132,13 +647,49 @@ impl Checker {
                     ret
                 }
             },
-            Type::Fun(a, b) => self.occurs(id, level, *a) || self.occurs(id, level, *b),
+            Type::Fun(a, b) | Type::RowExtend(_, a, b) => {
+                self.occurs(id, level, *a) || self.occurs(id, level, *b)
+            }
+            Type::List(elem) | Type::Record(elem) => self.occurs(id, level, *elem),
+            Type::Con(_, elems) | Type::Tuple(elems) => {
+                elems.into_iter().any(|elem| self.occurs(id, level, elem))
+            }
         }
     }
 
-    fn unify(&mut self, t1: Type, t2: Type) -> Result<(), Error> {
+    /// Unifies the type an expression was `expected` to have with the type it was `found` to have,
+    /// reporting a failure at `span`
+    fn expect(
+        &mut self,
+        expected: Type,
+        found: Type,
+        span: Span,
+        reason: Reason,
+    ) -> Result<(), Error> {
+        self.unify(expected.clone(), found.clone()).map_err(|err| {
+            let mut concretizer = Concretizer::new(self);
+            match err {
+                UnifyError::Mismatch => Error::NoUnify {
+                    expected: Box::new(concretizer.concretize(expected)),
+                    found: Box::new(concretizer.concretize(found)),
+                    span,
+                    reason,
+                },
+                UnifyError::Occurs(var, typ) => Error::Occurs {
+                    var: Box::new(concretizer.concretize(var)),
+                    typ: Box::new(concretizer.concretize(typ)),
+                    span,
+                    reason,
+                },
+            }
+        })
+    }
+
+    fn unify(&mut self, t1: Type, t2: Type) -> Result<(), UnifyError> {
         match (t1.clone(), t2.clone()) {
+            (Type::Error, _) | (_, Type::Error) => Ok(()),
             (Type::Int, Type::Int) => Ok(()),
+            (Type::Float, Type::Float) => Ok(()),
             (Type::Str, Type::Str) => Ok(()),
             (Type::Bool, Type::Bool) => Ok(()),
             (Type::Unit, Type::Unit) => Ok(()),
@@ -146,13 +697,50 @@ impl Checker {
                 self.unify(*a, *c)?;
                 self.unify(*b, *d)
             }
-            (Type::Var(tvar), b) if let TVar::Bound(a) = self.tvars.get(tvar) => self.unify(a.clone(), b),
-            (a, Type::Var(tvar)) if let TVar::Bound(b) = self.tvars.get(tvar) => self.unify(a, b.clone()),
+            (Type::Con(a, a_args), Type::Con(b, b_args))
+                if a == b && a_args.len() == b_args.len() =>
+            {
+                a_args
+                    .into_iter()
+                    .zip(b_args)
+                    .try_for_each(|(a, b)| self.unify(a, b))
+            }
+            (Type::Tuple(a_elems), Type::Tuple(b_elems)) if a_elems.len() == b_elems.len() => {
+                a_elems
+                    .into_iter()
+                    .zip(b_elems)
+                    .try_for_each(|(a, b)| self.unify(a, b))
+            }
+            (Type::List(a), Type::List(b)) | (Type::Record(a), Type::Record(b)) => {
+                self.unify(*a, *b)
+            }
+            (Type::RowEmpty, Type::RowEmpty) => Ok(()),
+            (Type::RowExtend(name, field, rest), row @ Type::RowExtend(..)) => {
+                // Find the same field in the other row, no matter where it is, and unify what's
+                // left of both rows
+                let tail = self.row_tail(&rest);
+                let (other_field, other_rest) = self.rewrite_row(row, &name)?;
+                // If finding the field extended our own tail, unifying the rests would extend it
+                // forever, e.g. for `{ a : Int | 'r }` and `{ b : Int | 'r }`
+                if let Some(tail) = tail {
+                    if let TVar::Bound(_) = self.tvars.get(tail) {
+                        return Err(UnifyError::Mismatch);
+                    }
+                }
+                self.unify(*field, other_field)?;
+                self.unify(*rest, other_rest)
+            }
+            (Type::Var(tvar), b) if let TVar::Bound(a) = self.tvars.get(tvar) => {
+                self.unify(a.clone(), b)
+            }
+            (a, Type::Var(tvar)) if let TVar::Bound(b) = self.tvars.get(tvar) => {
+                self.unify(a, b.clone())
+            }
             (Type::Var(tvar), b) if let TVar::Unbound(a_id, a_level) = self.tvars.get(tvar) => {
                 if t1 == t2 {
                     Ok(())
                 } else if self.occurs(*a_id, *a_level, b.clone()) {
-                    Err(Error::Occurs)
+                    Err(UnifyError::Occurs(t1, b))
                 } else {
                     self.tvars.set(tvar, TVar::Bound(b));
                     Ok(())
@@ -162,35 +750,232 @@ impl Checker {
                 if t1 == t2 {
                     Ok(())
                 } else if self.occurs(*b_id, *b_level, a.clone()) {
-                    Err(Error::Occurs)
+                    Err(UnifyError::Occurs(t2, a))
                 } else {
                     self.tvars.set(tvar, TVar::Bound(a));
                     Ok(())
                 }
             }
-            (t1, t2) => Err(Error::NoUnify(t1, t2)),
+            _ => Err(UnifyError::Mismatch),
+        }
+    }
+
+    /// Rearranges `row` to start with the field `name`, returning that field's type and the rest
+    /// of the row. If the row is open, the field is added to it.
+    fn rewrite_row(&mut self, row: Type, name: &str) -> Result<(Type, Type), UnifyError> {
+        match row {
+            Type::RowExtend(other, field, rest) if other == name => Ok((*field, *rest)),
+            Type::RowExtend(other, field, rest) => {
+                let (found, rest) = self.rewrite_row(*rest, name)?;
+                Ok((found, Type::RowExtend(other, field, Box::new(rest))))
+            }
+            Type::Var(tvar) => match self.tvars.get(tvar).clone() {
+                TVar::Bound(row) => self.rewrite_row(row, name),
+                TVar::Unbound(_, level) => {
+                    let field = self.new_tvar_at(level);
+                    let rest = self.new_tvar_at(level);
+                    let row = Type::RowExtend(
+                        name.to_string(),
+                        Box::new(field.clone()),
+                        Box::new(rest.clone()),
+                    );
+                    self.tvars.set(tvar, TVar::Bound(row));
+                    Ok((field, rest))
+                }
+            },
+            Type::Error => Ok((Type::Error, Type::Error)),
+            _ => Err(UnifyError::Mismatch),
         }
     }
 
-    pub fn check_let_value(&mut self, name: String, value: Expr, rec: bool) -> Result<(), Error> {
+    /// The tvar an open row ends in
+    fn row_tail(&self, row: &Type) -> Option<TVarRef> {
+        match row {
+            Type::RowExtend(_, _, rest) => self.row_tail(rest),
+            Type::Var(tvar) => match self.tvars.get(*tvar) {
+                TVar::Bound(row) => self.row_tail(row),
+                TVar::Unbound(..) => Some(*tvar),
+            },
+            _ => None,
+        }
+    }
+
+    pub fn check_let_value(
+        &mut self,
+        name: String,
+        value: Expr,
+        rec: bool,
+    ) -> Result<typed::Expr<Type>, Error> {
         self.enter_level();
-        let value_t = if rec {
+        let value = if rec {
             // In the expression `let rec v = e1 in e2`,
             // `e1` is checked with `v` bound to a fresh tvar in monotype position
             let value_t = self.new_tvar();
             self.bindings
                 .insert(name.clone(), Polytype(vec![], value_t.clone()));
-            self.check(value)?;
+            let value = self.check(value)?;
             self.bindings.remove(&name);
-            value_t
+            self.expect(
+                value_t,
+                value.ty.clone(),
+                value.span,
+                Reason::Recursive { name: name.clone() },
+            )?;
+            value
         } else {
             // Otherwise, we have `let v = e1 in e2` where referring to `v` in `e1` is illegal
             self.check(value)?
         };
         self.exit_level();
-        let poly = polymorphize(self, value_t);
+        let poly = polymorphize(self, value.ty.clone());
         self.bindings.insert(name, poly);
-        Ok(())
+        Ok(value)
+    }
+
+    /// Checks a top-level `let`. If it fails, the checker is restored to how it was before the
+    /// statement and `name` is bound to the error type, so that later statements using it don't
+    /// report follow-on errors.
+    pub fn check_top_level_let(
+        &mut self,
+        name: String,
+        value: Expr,
+        rec: bool,
+    ) -> Result<typed::Expr<Type>, Error> {
+        self.annotation_vars.clear();
+        let bindings = self.bindings.clone();
+        let cur_level = self.cur_level;
+        let result = self.check_let_value(name.clone(), value, rec);
+        if result.is_err() {
+            self.bindings = bindings;
+            self.cur_level = cur_level;
+            self.insert_error(name);
+        }
+        result
+    }
+
+    /// Checks a top-level `let` that destructures its value, binding each variable of `pattern`.
+    /// Like [Checker::check_top_level_let], if it fails the variables are all bound to the error
+    /// type.
+    pub fn check_top_level_let_pattern(
+        &mut self,
+        pattern: Pattern,
+        value: Expr,
+    ) -> Result<(typed::Pattern<Type>, typed::Expr<Type>), Error> {
+        self.annotation_vars.clear();
+        let bindings = self.bindings.clone();
+        let cur_level = self.cur_level;
+        let names: Vec<_> = pattern.vars().into_iter().map(str::to_string).collect();
+        let result = self.check_let_pattern(pattern, value);
+        if result.is_err() {
+            self.bindings = bindings;
+            self.cur_level = cur_level;
+            for name in names {
+                self.insert_error(name);
+            }
+        }
+        result
+    }
+
+    /// The variables of the pattern are generalized like the value of a `let` is, and the pattern
+    /// is checked for the values of its type that it doesn't match, like a `match` with one arm
+    fn check_let_pattern(
+        &mut self,
+        pattern: Pattern,
+        value: Expr,
+    ) -> Result<(typed::Pattern<Type>, typed::Expr<Type>), Error> {
+        self.enter_level();
+        let value = self.check(value)?;
+        let mut bound = Vec::new();
+        let pattern = self.check_pattern(pattern, &value.ty, &mut bound)?;
+        self.exit_level();
+        let warnings = exhaustive::check_match(self, [&pattern], value.span);
+        self.warnings.extend(warnings);
+        for (name, t) in bound {
+            let poly = polymorphize(self, t);
+            self.bindings.insert(name, poly);
+        }
+        Ok((pattern, value))
+    }
+
+    /// Adds a user-defined type and its constructors. The type is added before its constructors
+    /// are checked, so they can refer to it recursively. If a constructor's fields are invalid,
+    /// the constructor is still added with the error type, so uses of it don't report follow-on
+    /// errors.
+    pub fn define_type(&mut self, def: TypeDef) -> Result<Vec<(String, Polytype)>, Error> {
+        let TypeDef {
+            name: type_name,
+            params,
+            constructors,
+            ..
+        } = def;
+
+        let type_name = self.qualify(&type_name);
+        self.types.insert(
+            type_name.clone(),
+            DataType {
+                arity: params.len(),
+                constructors: constructors.iter().map(|c| self.qualify(&c.name)).collect(),
+            },
+        );
+
+        // The parameters are quantified in the type of each constructor
+        self.enter_level();
+        let mut vars = HashMap::new();
+        let mut args = Vec::new();
+        for param in params {
+            let t = self.new_tvar();
+            vars.insert(param, t.clone());
+            args.push(t);
+        }
+        let data_t = Type::Con(type_name.clone(), args);
+
+        let mut error = None;
+        let mut defined: Vec<(String, usize, Type)> = Vec::new();
+        for ConstructorDef { name, fields, span } in constructors {
+            if defined.iter().any(|(defined, _, _)| *defined == name) {
+                error.get_or_insert(Error::DuplicateConstructor { name, span });
+                continue;
+            }
+            let arity = fields.len();
+            let fields = fields
+                .into_iter()
+                .map(|field| checker_type_of_ast_type(self, field, &mut vars, false))
+                .collect::<Result<Vec<_>, _>>();
+            let t = match fields {
+                Ok(fields) => fields.into_iter().rev().fold(data_t.clone(), |t, field| {
+                    Type::Fun(Box::new(field), Box::new(t))
+                }),
+                Err(err) => {
+                    error.get_or_insert(err);
+                    Type::Error
+                }
+            };
+            defined.push((name, arity, t));
+        }
+        self.exit_level();
+
+        let mut constructor_types = Vec::new();
+        for (name, arity, t) in defined {
+            let poly = polymorphize(self, t);
+            self.constructors.insert(
+                self.qualify(&name),
+                Constructor {
+                    type_name: type_name.clone(),
+                    arity,
+                    poly: poly.clone(),
+                },
+            );
+            constructor_types.push((name, poly));
+        }
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(constructor_types),
+        }
+    }
+
+    pub fn insert_error(&mut self, name: String) {
+        self.bindings.insert(name, Polytype(vec![], Type::Error));
     }
 
     pub fn insert_declare(&mut self, name: String, typ: Type) {
@@ -198,11 +983,40 @@ impl Checker {
         self.bindings.insert(name, poly);
     }
 
-    pub fn get_bindings(&mut self) -> HashMap<String, Type> {
-        self.bindings
-            .clone()
-            .into_iter()
-            .map(|(name, typ)| (name, monomorphize(self, typ)))
-            .collect()
+    /// Checks an expression on its own, generalizing over any tvars left unbound
+    pub fn check_generalized(
+        &mut self,
+        expr: Expr,
+    ) -> Result<(Polytype, typed::Expr<Type>), Error> {
+        self.enter_level();
+        let expr = self.check(expr);
+        self.exit_level();
+        let expr = expr?;
+        Ok((polymorphize(self, expr.ty.clone()), expr))
+    }
+}
+
+/// The row with the given fields, in order, followed by `rest`
+pub(super) fn row(fields: impl IntoIterator<Item = (String, Type)>, rest: Type) -> Type {
+    let fields: Vec<_> = fields.into_iter().collect();
+    fields.into_iter().rev().fold(rest, |row, (name, field)| {
+        Type::RowExtend(name, Box::new(field), Box::new(row))
+    })
+}
+
+/// Reports the first field whose name was already given
+pub(super) fn check_distinct_fields<'a>(
+    fields: impl IntoIterator<Item = (&'a String, Span)>,
+) -> Result<(), Error> {
+    let mut names = Vec::new();
+    for (name, span) in fields {
+        if names.contains(&name) {
+            return Err(Error::DuplicateField {
+                name: name.clone(),
+                span,
+            });
+        }
+        names.push(name);
     }
+    Ok(())
 }
@@ -0,0 +1,109 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use super::{
+    check::Checker,
+    ty::{Scheme, Ty},
+    Polytype, TVar, TVarRef, Type,
+};
+
+/// Turns the checker's internal types into [Ty]s by resolving bound tvars and naming the unbound
+/// ones 'a, 'b, ... in order of appearance. Reusing one instance across several types keeps the
+/// names consistent between them.
+pub(super) struct Concretizer<'a> {
+    checker: &'a Checker,
+    names: HashMap<TVarRef, String>,
+    order: Vec<TVarRef>,
+}
+
+impl<'a> Concretizer<'a> {
+    pub fn new(checker: &'a Checker) -> Concretizer<'a> {
+        Concretizer {
+            checker,
+            names: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    pub fn concretize(&mut self, typ: Type) -> Ty {
+        match typ {
+            Type::Int => Ty::Int,
+            Type::Float => Ty::Float,
+            Type::Str => Ty::Str,
+            Type::Unit => Ty::Unit,
+            Type::Bool => Ty::Bool,
+            Type::Error => Ty::Error,
+            Type::Var(var) => match self.checker.tvars.get(var) {
+                TVar::Bound(t) => self.concretize(t.clone()),
+                TVar::Unbound(var_ref, _) => Ty::Var(self.var_name(*var_ref)),
+            },
+            Type::Fun(a, b) => {
+                Ty::Fun(Box::new(self.concretize(*a)), Box::new(self.concretize(*b)))
+            }
+            Type::Con(name, args) => Ty::Con(
+                self.checker.display_name(&name, &self.checker.types),
+                args.into_iter().map(|arg| self.concretize(arg)).collect(),
+            ),
+            Type::Tuple(elems) => Ty::Tuple(
+                elems
+                    .into_iter()
+                    .map(|elem| self.concretize(elem))
+                    .collect(),
+            ),
+            Type::List(elem) => Ty::List(Box::new(self.concretize(*elem))),
+            Type::Record(row) => {
+                let (fields, rest) = self.concretize_row(*row);
+                Ty::Record(fields, rest)
+            }
+            Type::RowEmpty | Type::RowExtend(..) => unreachable!("rows only appear in records"),
+        }
+    }
+
+    /// The fields of a row, sorted by name, and the name of the tvar it ends in, if it's open
+    fn concretize_row(&mut self, mut row: Type) -> (Vec<(String, Ty)>, Option<String>) {
+        let mut fields = Vec::new();
+        let rest = loop {
+            match row {
+                Type::RowExtend(name, field, rest) => {
+                    fields.push((name, self.concretize(*field)));
+                    row = *rest;
+                }
+                Type::Var(var) => match self.checker.tvars.get(var) {
+                    TVar::Bound(t) => row = t.clone(),
+                    TVar::Unbound(var_ref, _) => break Some(self.var_name(*var_ref)),
+                },
+                _ => break None,
+            }
+        };
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        (fields, rest)
+    }
+
+    pub fn concretize_polytype(&mut self, poly: Polytype) -> Scheme {
+        let Polytype(quantified, typ) = poly;
+        let ty = self.concretize(typ);
+        let vars = self
+            .order
+            .iter()
+            .filter(|var_ref| quantified.contains(var_ref))
+            .map(|var_ref| self.names[var_ref].clone())
+            .collect();
+        Scheme { vars, ty }
+    }
+
+    fn var_name(&mut self, var_ref: TVarRef) -> String {
+        let i = self.names.len();
+        match self.names.entry(var_ref) {
+            Entry::Occupied(o) => o.get().clone(),
+            Entry::Vacant(v) => {
+                let letter = (b'a' + (i % 26) as u8) as char;
+                let name = match i / 26 {
+                    0 => letter.to_string(),
+                    n => format!("{}{}", letter, n),
+                };
+                v.insert(name.clone());
+                self.order.push(var_ref);
+                name
+            }
+        }
+    }
+}
@@ -1,20 +1,396 @@
-use crate::Type;
+use pandalang_parser::{diagnostic::Diagnostic, span::Span};
+
+use crate::Ty;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
 pub enum Error {
-    NotInScope { name: String },
-    NoUnify(Type, Type),
-    Occurs,
-    UnknownType { name: String },
+    NotInScope {
+        name: String,
+        span: Span,
+    },
+    NoUnify {
+        expected: Box<Ty>,
+        found: Box<Ty>,
+        span: Span,
+        reason: Reason,
+    },
+    Occurs {
+        var: Box<Ty>,
+        typ: Box<Ty>,
+        span: Span,
+        reason: Reason,
+    },
+    UnknownType {
+        name: String,
+        span: Span,
+    },
+    /// A type variable in the fields of a `type` definition isn't one of its parameters
+    UnknownTypeVar {
+        name: String,
+        span: Span,
+    },
+    TypeArity {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    UnknownConstructor {
+        name: String,
+        span: Span,
+    },
+    DuplicateConstructor {
+        name: String,
+        span: Span,
+    },
+    ConstructorArity {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    /// A record, record type or record pattern names the same field more than once
+    DuplicateField {
+        name: String,
+        span: Span,
+    },
+    /// A pattern binds the same variable more than once
+    DuplicatePatternVar {
+        name: String,
+        span: Span,
+    },
+    /// A qualified name refers to a module that isn't imported
+    ModuleNotImported {
+        module: String,
+        span: Span,
+    },
+    /// An `import` in a program that is checked on its own, rather than loaded from a file with
+    /// the modules it imports
+    UnresolvedImport {
+        module: String,
+        span: Span,
+    },
+    /// The program is run but doesn't define `main`
+    MissingMain,
+    /// `main` is defined, but running it wouldn't produce an exit code
+    MainType {
+        found: Ty,
+        span: Span,
+    },
+}
+
+/// Why the checker expected an expression to have a particular type
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub enum Reason {
+    /// The expression is applied to an argument, so it must be a function
+    Applied,
+    /// The expression is the argument of a function application
+    Argument,
+    /// The expression is an operand of an arithmetic operator
+    Operand { op: String },
+    /// The expression is the right operand of `op`, i.e. `==` or `!=`, so it must match the left
+    Equality { op: String },
+    /// The expression is negated by `not`
+    Not,
+    /// The expression is the condition of an `if`
+    IfCondition,
+    /// The expression is the `else` branch of an `if`, so it must match the `then` branch
+    IfBranches,
+    /// The expression is the definition of `name`, which refers to itself
+    Recursive { name: String },
+    /// The expression is annotated with a type
+    Annotation,
+    /// The pattern is matched against a value
+    Pattern,
+    /// The expression is an arm of a `match`, so it must match the other arms
+    MatchArms,
+    /// The expression is an element of a list, so it must match the other elements
+    ListElems,
+    /// The expression is the right operand of `::`, so it must be a list of the left operand's
+    /// type
+    Cons,
+    /// A field of the expression is accessed
+    Field { name: String },
+    /// The expression is a record whose fields are replaced in a `with`
+    Update,
+}
+
+impl Reason {
+    fn note(&self, expected: &Ty) -> String {
+        match self {
+            Reason::Applied => {
+                "this expression is applied to an argument, so it is expected to be a function"
+                    .to_string()
+            }
+            Reason::Argument => format!(
+                "the function being applied expects an argument of type {}",
+                expected
+            ),
+            Reason::Operand { op } => format!(
+                "the operands of `{}` are expected to have type {}",
+                op, expected
+            ),
+            Reason::Equality { op } => {
+                format!("both sides of `{}` are expected to have the same type", op)
+            }
+            Reason::Not => format!(
+                "the operand of `not` is expected to have type {}",
+                expected
+            ),
+            Reason::IfCondition => format!(
+                "the condition of an `if` is expected to have type {}",
+                expected
+            ),
+            Reason::IfBranches => {
+                "both branches of an `if` are expected to have the same type".to_string()
+            }
+            Reason::Recursive { name } => format!(
+                "`{}` is used within its own definition, so the definition is expected to have the type of those uses",
+                name
+            ),
+            Reason::Pattern => format!(
+                "this pattern is matched against a value of type {}",
+                expected
+            ),
+            Reason::Field { name } => format!(
+                "the field `{}` is accessed, so this is expected to be a record with that field",
+                name
+            ),
+            Reason::Update => {
+                "a `with` is expected to replace fields the record has with values of the same type"
+                    .to_string()
+            }
+            Reason::ListElems => {
+                "all elements of a list are expected to have the same type".to_string()
+            }
+            Reason::Cons => format!(
+                "`::` adds an element to the front of a list, so its right operand is expected to have type {}",
+                expected
+            ),
+            Reason::MatchArms => {
+                "all arms of a `match` are expected to have the same type".to_string()
+            }
+            Reason::Annotation => format!(
+                "the expression is annotated with type {}, so it is expected to have that type",
+                expected
+            ),
+        }
+    }
+}
+
+impl Error {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::NotInScope { span, .. }
+            | Error::NoUnify { span, .. }
+            | Error::Occurs { span, .. }
+            | Error::UnknownType { span, .. }
+            | Error::UnknownTypeVar { span, .. }
+            | Error::TypeArity { span, .. }
+            | Error::UnknownConstructor { span, .. }
+            | Error::DuplicateConstructor { span, .. }
+            | Error::ConstructorArity { span, .. }
+            | Error::DuplicatePatternVar { span, .. }
+            | Error::DuplicateField { span, .. }
+            | Error::ModuleNotImported { span, .. }
+            | Error::UnresolvedImport { span, .. }
+            | Error::MainType { span, .. } => Some(*span),
+            Error::MissingMain => None,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Error::NotInScope { name, span } => {
+                Diagnostic::error(format!("{} is not in scope", name))
+                    .with_label(*span, "not found in this scope")
+            }
+            Error::NoUnify {
+                expected,
+                found,
+                span,
+                reason,
+            } => Diagnostic::error("mismatched types")
+                .with_label(*span, format!("expected {}, found {}", expected, found))
+                .with_note(reason.note(expected)),
+            Error::Occurs {
+                var,
+                typ,
+                span,
+                reason,
+            } => Diagnostic::error("infinite type")
+                .with_label(
+                    *span,
+                    format!(
+                        "{} would have to be equal to {}, which contains it",
+                        var, typ
+                    ),
+                )
+                .with_note(reason.note(var)),
+            Error::UnknownType { name, span } => {
+                Diagnostic::error(format!("{} is not a known type", name))
+                    .with_label(*span, "unknown type")
+            }
+            Error::UnknownTypeVar { name, span } => {
+                Diagnostic::error(format!("type variable '{} is not in scope", name))
+                    .with_label(*span, "not a parameter of the type being defined")
+            }
+            Error::TypeArity {
+                name,
+                expected,
+                found,
+                span,
+            } => Diagnostic::error(format!("wrong number of arguments for type {}", name))
+                .with_label(
+                    *span,
+                    format!(
+                        "expected {} argument{}, found {}",
+                        expected,
+                        if *expected == 1 { "" } else { "s" },
+                        found
+                    ),
+                ),
+            Error::UnknownConstructor { name, span } => {
+                Diagnostic::error(format!("constructor {} is not in scope", name))
+                    .with_label(*span, "not found in this scope")
+            }
+            Error::DuplicateConstructor { name, span } => {
+                Diagnostic::error(format!("constructor {} is defined more than once", name))
+                    .with_label(*span, "already defined in this type")
+            }
+            Error::ConstructorArity {
+                name,
+                expected,
+                found,
+                span,
+            } => Diagnostic::error(format!("wrong number of fields for constructor {}", name))
+                .with_label(
+                    *span,
+                    format!(
+                        "expected {} field{}, found {}",
+                        expected,
+                        if *expected == 1 { "" } else { "s" },
+                        found
+                    ),
+                ),
+            Error::DuplicateField { name, span } => {
+                Diagnostic::error(format!("field {} is given more than once", name))
+                    .with_label(*span, "already given in this record")
+            }
+            Error::DuplicatePatternVar { name, span } => {
+                Diagnostic::error(format!("{} is bound more than once in the same pattern", name))
+                    .with_label(*span, "already bound in this pattern")
+            }
+            Error::ModuleNotImported { module, span } => {
+                Diagnostic::error(format!("module {} is not imported", module))
+                    .with_label(*span, format!("add `import {}` to use this", module))
+            }
+            Error::UnresolvedImport { module, span } => {
+                Diagnostic::error(format!("module {} can't be imported here", module))
+                    .with_label(*span, "imports are only resolved when running a file")
+            }
+            Error::MissingMain => Diagnostic::error("no `main` is defined")
+                .with_note("programs start by evaluating a top-level `let main = ...`"),
+            Error::MainType { found, span } => Diagnostic::error("`main` has the wrong type")
+                .with_label(*span, format!("expected Unit or Int, found {}", found))
+                .with_note("the value of `main` is the program's exit code, so it is expected to have type Unit or Int"),
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::NotInScope { name } => write!(f, "{} is not in scope", name),
-            Error::NoUnify(t1, t2) => write!(f, "Could not unify {:?} with {:?}", t1, t2),
-            Error::Occurs => write!(f, "Occurs check failed"),
-            Error::UnknownType { name } => write!(f, "{} is not a known type", name),
+            Error::NotInScope { name, .. } => write!(f, "{} is not in scope", name),
+            Error::NoUnify {
+                expected, found, ..
+            } => write!(f, "Expected {}, found {}", expected, found),
+            Error::Occurs { var, typ, .. } => {
+                write!(f, "Occurs check failed: {} occurs in {}", var, typ)
+            }
+            Error::UnknownType { name, .. } => write!(f, "{} is not a known type", name),
+            Error::UnknownTypeVar { name, .. } => {
+                write!(f, "Type variable '{} is not in scope", name)
+            }
+            Error::TypeArity {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "Type {} expects {} arguments, found {}",
+                name, expected, found
+            ),
+            Error::UnknownConstructor { name, .. } => {
+                write!(f, "Constructor {} is not in scope", name)
+            }
+            Error::DuplicateConstructor { name, .. } => {
+                write!(f, "Constructor {} is defined more than once", name)
+            }
+            Error::ConstructorArity {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "Constructor {} expects {} fields, found {}",
+                name, expected, found
+            ),
+            Error::DuplicateField { name, .. } => {
+                write!(f, "Field {} is given more than once", name)
+            }
+            Error::DuplicatePatternVar { name, .. } => {
+                write!(f, "{} is bound more than once in the same pattern", name)
+            }
+            Error::ModuleNotImported { module, .. } => {
+                write!(f, "Module {} is not imported", module)
+            }
+            Error::UnresolvedImport { module, .. } => {
+                write!(f, "Module {} can't be imported here", module)
+            }
+            Error::MissingMain => write!(f, "No main is defined"),
+            Error::MainType { found, .. } => {
+                write!(f, "Expected main to have type Unit or Int, found {}", found)
+            }
+        }
+    }
+}
+
+/// Something suspicious about a program that still type checks
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub enum Warning {
+    /// A `match` doesn't have an arm for every value, e.g. `missing` is `Some (Cons _ _)`
+    NonExhaustive { missing: String, span: Span },
+    /// An arm of a `match` only matches values that earlier arms already match
+    UnreachableArm { span: Span },
+}
+
+impl Warning {
+    pub fn span(&self) -> Span {
+        match self {
+            Warning::NonExhaustive { span, .. } | Warning::UnreachableArm { span } => *span,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Warning::NonExhaustive { missing, span } => Diagnostic::warning("non-exhaustive match")
+                .with_label(*span, format!("`{}` not covered", missing)),
+            Warning::UnreachableArm { span } => Diagnostic::warning("unreachable match arm")
+                .with_label(*span, "already matched by the arms above"),
+        }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::NonExhaustive { missing, .. } => {
+                write!(f, "Match is not exhaustive: {} is not covered", missing)
+            }
+            Warning::UnreachableArm { .. } => write!(f, "Match arm is unreachable"),
         }
     }
 }
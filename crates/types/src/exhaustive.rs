@@ -0,0 +1,317 @@
+// Checks that the arms of a `match` cover every value, and that each arm can be reached
+// Based on "Warnings for pattern matching" by Luc Maranget: http://moscova.inria.fr/~maranget/papers/warn/warn.pdf
+
+use std::iter::{once, repeat_n};
+
+use pandalang_parser::span::Span;
+
+use super::{
+    check::Checker,
+    error::Warning,
+    typed::{self, PatternKind},
+    TVar, Type,
+};
+
+/// A pattern stripped of what doesn't affect which values it matches, like variable names
+#[derive(Clone)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+}
+
+/// The head of a value, which a pattern can test for
+#[derive(PartialEq, Eq, Clone)]
+enum Ctor {
+    Data(String),
+    Int(i64),
+    Str(String),
+    Unit,
+    Bool(bool),
+    /// A tuple with this many elements
+    Tuple(usize),
+    /// The empty list
+    Nil,
+    /// A non-empty list, whose fields are its first element and the rest of the list
+    Cons,
+    /// A record with these fields, sorted by name
+    Record(Vec<String>),
+}
+
+/// A row of the pattern matrix, i.e. one pattern for each part of the value still to be matched
+type Row = Vec<Pat>;
+
+impl Pat {
+    fn from_typed(checker: &Checker, pattern: &typed::Pattern<Type>) -> Pat {
+        let from_typed = |pattern| Pat::from_typed(checker, pattern);
+        match &pattern.kind {
+            PatternKind::Wildcard | PatternKind::Var(_) => Pat::Wild,
+            PatternKind::Int(n) => Pat::Ctor(Ctor::Int(*n), Vec::new()),
+            PatternKind::Str(s) => Pat::Ctor(Ctor::Str(s.clone()), Vec::new()),
+            PatternKind::Unit => Pat::Ctor(Ctor::Unit, Vec::new()),
+            PatternKind::Bool(b) => Pat::Ctor(Ctor::Bool(*b), Vec::new()),
+            PatternKind::Constructor { name, args } => Pat::Ctor(
+                Ctor::Data(name.clone()),
+                args.iter().map(from_typed).collect(),
+            ),
+            PatternKind::Tuple(elems) => Pat::Ctor(
+                Ctor::Tuple(elems.len()),
+                elems.iter().map(from_typed).collect(),
+            ),
+            // `[x, y]` is `x :: y :: []`
+            PatternKind::List(elems) => elems
+                .iter()
+                .rev()
+                .fold(Pat::Ctor(Ctor::Nil, Vec::new()), |tail, elem| {
+                    Pat::Ctor(Ctor::Cons, vec![from_typed(elem), tail])
+                }),
+            PatternKind::Cons { head, tail } => {
+                Pat::Ctor(Ctor::Cons, vec![from_typed(head), from_typed(tail)])
+            }
+            // Every record pattern in the same position has the same type, so the fields it has
+            // are the same for all of them, even if each pattern only mentions some
+            PatternKind::Record(fields) => {
+                let names = record_fields(checker, &pattern.ty);
+                let args = names
+                    .iter()
+                    .map(|name| {
+                        fields
+                            .iter()
+                            .find(|(field, _)| field == name)
+                            .map_or(Pat::Wild, |(_, pattern)| from_typed(pattern))
+                    })
+                    .collect();
+                Pat::Ctor(Ctor::Record(names), args)
+            }
+        }
+    }
+}
+
+impl Pat {
+    /// The pattern with its constructors named the way the module being checked reports them
+    fn named(&self, checker: &Checker) -> Pat {
+        match self {
+            Pat::Wild => Pat::Wild,
+            Pat::Ctor(ctor, args) => {
+                let ctor = match ctor {
+                    Ctor::Data(name) => {
+                        Ctor::Data(checker.display_name(name, &checker.constructors))
+                    }
+                    ctor => ctor.clone(),
+                };
+                Pat::Ctor(ctor, args.iter().map(|arg| arg.named(checker)).collect())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Pat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Ctor(Ctor::Data(name), args) => {
+                write!(f, "{}", name)?;
+                for arg in args {
+                    match arg {
+                        Pat::Ctor(_, args) if !args.is_empty() => write!(f, " ({})", arg)?,
+                        _ => write!(f, " {}", arg)?,
+                    }
+                }
+                Ok(())
+            }
+            Pat::Ctor(Ctor::Int(n), _) => write!(f, "{}", n),
+            Pat::Ctor(Ctor::Str(s), _) => write!(f, "{:?}", s),
+            Pat::Ctor(Ctor::Unit, _) => write!(f, "()"),
+            Pat::Ctor(Ctor::Bool(b), _) => write!(f, "{}", b),
+            Pat::Ctor(Ctor::Tuple(_), elems) => {
+                let elems: Vec<_> = elems.iter().map(|elem| elem.to_string()).collect();
+                write!(f, "({})", elems.join(", "))
+            }
+            Pat::Ctor(Ctor::Nil, _) => write!(f, "[]"),
+            Pat::Ctor(Ctor::Cons, args) => match args.as_slice() {
+                [head @ Pat::Ctor(Ctor::Cons, _), tail] => write!(f, "({}) :: {}", head, tail),
+                [head, tail] => write!(f, "{} :: {}", head, tail),
+                _ => unreachable!("a cons has a head and a tail"),
+            },
+            Pat::Ctor(Ctor::Record(names), fields) => {
+                let fields: Vec<_> = names
+                    .iter()
+                    .zip(fields)
+                    .map(|(name, field)| format!("{} = {}", name, field))
+                    .collect();
+                write!(f, "{{ {} }}", fields.join("; "))
+            }
+        }
+    }
+}
+
+/// Warns about the patterns of the arms of a `match` on the expression at `span` that can never
+/// be reached, and about the match if some value isn't matched by any of them
+pub(super) fn check_match<'a>(
+    checker: &Checker,
+    patterns: impl IntoIterator<Item = &'a typed::Pattern<Type>>,
+    span: Span,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut rows = Vec::new();
+    for pattern in patterns {
+        let row = vec![Pat::from_typed(checker, pattern)];
+        if !useful(checker, &rows, &row) {
+            warnings.push(Warning::UnreachableArm { span: pattern.span });
+        }
+        rows.push(row);
+    }
+    if let Some(witness) = missing(checker, &rows, 1) {
+        warnings.push(Warning::NonExhaustive {
+            missing: witness[0].named(checker).to_string(),
+            span,
+        });
+    }
+    warnings
+}
+
+/// Whether some value matched by `row` isn't matched by any of `rows`
+fn useful(checker: &Checker, rows: &[Row], row: &[Pat]) -> bool {
+    let Some((head, rest)) = row.split_first() else {
+        return rows.is_empty();
+    };
+    match head {
+        Pat::Ctor(ctor, args) => {
+            let row: Row = args.iter().chain(rest).cloned().collect();
+            useful(checker, &specialize(rows, ctor, args.len()), &row)
+        }
+        Pat::Wild => match complete(checker, &head_ctors(rows)) {
+            Some(ctors) => ctors.iter().any(|ctor| {
+                let arity = arity(checker, ctor);
+                let row: Row = repeat_n(Pat::Wild, arity).chain(rest.to_vec()).collect();
+                useful(checker, &specialize(rows, ctor, arity), &row)
+            }),
+            None => useful(checker, &default(rows), rest),
+        },
+    }
+}
+
+/// `n` patterns that together match a value none of `rows` match, if there is one
+fn missing(checker: &Checker, rows: &[Row], n: usize) -> Option<Row> {
+    if n == 0 {
+        return rows.is_empty().then(Vec::new);
+    }
+    let heads = head_ctors(rows);
+    match complete(checker, &heads) {
+        Some(ctors) => ctors.into_iter().find_map(|ctor| {
+            let arity = arity(checker, &ctor);
+            let mut args = missing(checker, &specialize(rows, &ctor, arity), arity + n - 1)?;
+            let rest = args.split_off(arity);
+            Some(once(Pat::Ctor(ctor, args)).chain(rest).collect())
+        }),
+        None => {
+            let rest = missing(checker, &default(rows), n - 1)?;
+            // Name a constructor none of the rows start with if we can, to make the example
+            // more helpful than `_`
+            let head = heads
+                .first()
+                .and_then(|ctor| siblings(checker, ctor))
+                .and_then(|ctors| ctors.into_iter().find(|ctor| !heads.contains(ctor)))
+                .map_or(Pat::Wild, |ctor| {
+                    let arity = arity(checker, &ctor);
+                    Pat::Ctor(ctor, vec![Pat::Wild; arity])
+                });
+            Some(once(head).chain(rest).collect())
+        }
+    }
+}
+
+/// The rows that can match a value starting with `ctor`, with their first pattern replaced by
+/// the patterns for its fields
+fn specialize(rows: &[Row], ctor: &Ctor, arity: usize) -> Vec<Row> {
+    rows.iter()
+        .filter_map(|row| match &row[0] {
+            Pat::Wild => Some(
+                repeat_n(Pat::Wild, arity)
+                    .chain(row[1..].iter().cloned())
+                    .collect(),
+            ),
+            Pat::Ctor(head, args) if head == ctor => {
+                Some(args.iter().chain(&row[1..]).cloned().collect())
+            }
+            Pat::Ctor(..) => None,
+        })
+        .collect()
+}
+
+/// The rows whose first pattern matches anything, without that pattern
+fn default(rows: &[Row]) -> Vec<Row> {
+    rows.iter()
+        .filter(|row| matches!(row[0], Pat::Wild))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// The distinct constructors the rows' first patterns test for
+fn head_ctors(rows: &[Row]) -> Vec<Ctor> {
+    let mut heads = Vec::new();
+    for row in rows {
+        if let Pat::Ctor(ctor, _) = &row[0] {
+            if !heads.contains(ctor) {
+                heads.push(ctor.clone());
+            }
+        }
+    }
+    heads
+}
+
+/// Every constructor of the type, if `heads` includes all of them
+fn complete(checker: &Checker, heads: &[Ctor]) -> Option<Vec<Ctor>> {
+    let ctors = siblings(checker, heads.first()?)?;
+    ctors
+        .iter()
+        .all(|ctor| heads.contains(ctor))
+        .then_some(ctors)
+}
+
+/// Every constructor of the type `ctor` belongs to, or None if there are infinitely many
+fn siblings(checker: &Checker, ctor: &Ctor) -> Option<Vec<Ctor>> {
+    match ctor {
+        Ctor::Data(name) => {
+            let type_name = &checker.constructors[name].type_name;
+            let ctors = &checker.types[type_name].constructors;
+            Some(ctors.iter().cloned().map(Ctor::Data).collect())
+        }
+        Ctor::Bool(_) => Some(vec![Ctor::Bool(true), Ctor::Bool(false)]),
+        Ctor::Unit => Some(vec![Ctor::Unit]),
+        Ctor::Nil | Ctor::Cons => Some(vec![Ctor::Nil, Ctor::Cons]),
+        Ctor::Tuple(_) | Ctor::Record(_) => Some(vec![ctor.clone()]),
+        Ctor::Int(_) | Ctor::Str(_) => None,
+    }
+}
+
+fn arity(checker: &Checker, ctor: &Ctor) -> usize {
+    match ctor {
+        Ctor::Data(name) => checker.constructors[name].arity,
+        Ctor::Tuple(n) => *n,
+        Ctor::Cons => 2,
+        Ctor::Record(names) => names.len(),
+        _ => 0,
+    }
+}
+
+/// The names of the fields a record type is known to have, sorted
+fn record_fields(checker: &Checker, t: &Type) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut t = t;
+    loop {
+        match t {
+            Type::Var(var) => match checker.tvars.get(*var) {
+                TVar::Bound(bound) => t = bound,
+                TVar::Unbound(..) => break,
+            },
+            Type::Record(row) => t = row,
+            Type::RowExtend(name, _, rest) => {
+                names.push(name.clone());
+                t = rest;
+            }
+            _ => break,
+        }
+    }
+    names.sort();
+    names
+}
@@ -1,30 +1,60 @@
-#![feature(if_let_guard)]
-
-use pandalang_parser::ast::{
-    self,
-    expr::Expr,
-    stmt::{self, Stmt},
-    Program,
+use pandalang_parser::{
+    ast::{
+        self,
+        expr::Expr,
+        stmt::{self, Stmt},
+        Program,
+    },
+    module::{ModuleError, ModuleGraph, ModuleId},
+    prelude,
+    span::Span,
 };
 
-use self::{check::Checker, error::Error};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use self::{
+    check::{check_distinct_fields, row, Checker},
+    concretize::Concretizer,
+};
+pub use self::{
+    error::{Error, Reason, Warning},
+    ty::{Scheme, Ty},
+};
 
 mod check;
+mod concretize;
 mod error;
+mod exhaustive;
 mod monomorphize;
 mod polymorphize;
-mod string_of_type;
 mod tvars;
+mod ty;
+pub mod typed;
 
-// TODO: this shouldn't be public, see comment on check_expr_to_string
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
-pub enum Type {
+enum Type {
     Int,
+    Float,
     Str,
     Unit,
     Bool,
+    /// The type of an expression that failed to check. It unifies with every type, so one
+    /// mistake doesn't cascade into errors everywhere it is used.
+    Error,
     Var(TVarRef),
     Fun(Box<Type>, Box<Type>),
+    /// A user-defined type applied to its arguments
+    Con(String, Vec<Type>),
+    Tuple(Vec<Type>),
+    /// The built-in `List` type
+    List(Box<Type>),
+    /// A record whose fields are given by a row, i.e. a `RowEmpty` extended with a `RowExtend` for
+    /// each field. A row ending in a tvar instead can be extended with more fields by
+    /// unification, which is how `fun r -> r.name` accepts any record with a `name` field.
+    Record(Box<Type>),
+    RowEmpty,
+    /// A row with a field added, from the field's name, its type, and the rest of the row
+    RowExtend(String, Box<Type>, Box<Type>),
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
@@ -33,9 +63,8 @@ enum TVar {
     Unbound(TVarRef, Level),
 }
 
-// TODO: this shouldn't be public, see comment on check_expr_to_string
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
-pub struct TVarRef(usize);
+struct TVarRef(usize);
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
 struct Level(usize);
@@ -43,62 +72,561 @@ struct Level(usize);
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
 struct Polytype(Vec<TVarRef>, Type);
 
-// TODO: It shouldn't be necessary to export a special function like this.
-// This module should have a public Type type that can be consumed by
-// other modules. This shouldn't be the existing Type type (i.e., that should)
-// remain private to this module, because that would expose all of the
-// gross internals of type checking (which could get more gross over time).
-// string_of_type does an interesting thing where it kind of "concretizes" a
-// type, replacing tvars with actual types... it just happens to then turn them
-// all to strings too. We should instead have some "Concretizer" that does this
-// process and whatever it spits out can be that exported Type type. We need to
-// decide how best to represent unbound tvars for that type first.
+/// A user-defined type, as introduced by a `type` definition
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct DataType {
+    arity: usize,
+    /// The names of its constructors, in the order they were defined
+    constructors: Vec<String>,
+}
+
+/// A constructor of a user-defined type. Its type is a function from its fields to the type it
+/// constructs, e.g. `'a -> List 'a -> List 'a` for `Cons`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Constructor {
+    type_name: String,
+    arity: usize,
+    poly: Polytype,
+}
+
+/// The result of successfully checking a program
+#[derive(PartialEq, Debug, Clone)]
+pub struct CheckedProgram {
+    /// The type of every top-level binding, including `declare`d ones
+    pub bindings: BTreeMap<String, Scheme>,
+    /// The program with every expression annotated with its type
+    pub program: typed::Program,
+    /// Problems that don't stop the program from type checking, in the order they appear
+    pub warnings: Vec<Warning>,
+}
+
+/// What is in scope in a program besides its own definitions and the modules it imports
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Globals {
+    /// Whether the definitions of the prelude are in scope
+    pub prelude: bool,
+    /// The types of the builtins the host running the program provides, which it can use without
+    /// declaring them
+    pub builtins: Vec<(String, ast::types::Type)>,
+}
+
+impl Default for Globals {
+    fn default() -> Self {
+        Globals {
+            prelude: true,
+            builtins: Vec::new(),
+        }
+    }
+}
+
+/// Infers the most general type of a standalone expression
+pub fn check_expr(ast: Expr) -> Result<Scheme, Error> {
+    let mut checker = checker_with_prelude();
+    let (poly, _) = checker.check_generalized(ast)?;
+    Ok(Concretizer::new(&checker).concretize_polytype(poly))
+}
+
+/// Infers the type of a standalone expression and of each of its subexpressions
+pub fn check_expr_typed(ast: Expr) -> Result<typed::Expr, Error> {
+    let mut checker = checker_with_prelude();
+    let (poly, expr) = checker.check_generalized(ast)?;
+    let mut concretizer = Concretizer::new(&checker);
+    concretizer.concretize_polytype(poly);
+    Ok(expr.map_types(&mut |t| concretizer.concretize(t)))
+}
+
 pub fn check_expr_to_string(ast: Expr) -> Result<String, Error> {
-    let mut checker = Checker::new();
-    let typ = checker.check(ast)?;
-    Ok(string_of_type::string_of_type(&checker, typ))
+    check_expr(ast).map(|scheme| scheme.to_string())
 }
 
-fn checker_type_of_ast_type(ast_type: ast::types::Type) -> Result<Type, Error> {
+/// Translates a type written in the source. Each type variable is looked up in `vars`. If
+/// `new_vars` is set, its first occurrence is given a fresh tvar at the checker's current level,
+/// otherwise it must already be there.
+pub(crate) fn checker_type_of_ast_type(
+    checker: &mut Checker,
+    ast_type: ast::types::Type,
+    vars: &mut HashMap<String, Type>,
+    new_vars: bool,
+) -> Result<Type, Error> {
     match ast_type {
-        ast::types::Type::Simple(name) => match name.as_str() {
-            "Int" => Ok(Type::Int),
-            "Str" => Ok(Type::Str),
-            "Unit" => Ok(Type::Unit),
-            "Bool" => Ok(Type::Bool),
-            _ => Err(Error::UnknownType { name }),
+        ast::types::Type::Simple(ast::types::Simple { name, span }) => {
+            checker_type_of_type_app(checker, name, Vec::new(), span)
+        }
+        ast::types::Type::App(ast::types::App { name, args, span }) => {
+            let args = args
+                .into_iter()
+                .map(|arg| checker_type_of_ast_type(checker, arg, vars, new_vars))
+                .collect::<Result<_, _>>()?;
+            checker_type_of_type_app(checker, name, args, span)
+        }
+        ast::types::Type::Var(ast::types::Var { name, span }) => match vars.get(&name) {
+            Some(t) => Ok(t.clone()),
+            None if new_vars => {
+                let t = checker.new_tvar();
+                vars.insert(name, t.clone());
+                Ok(t)
+            }
+            None => Err(Error::UnknownTypeVar { name, span }),
         },
-        ast::types::Type::Fun(ast::types::Fun { from, to }) => Ok(Type::Fun(
-            Box::new(checker_type_of_ast_type(*from)?),
-            Box::new(checker_type_of_ast_type(*to)?),
+        ast::types::Type::Tuple(ast::types::Tuple { elems, .. }) => Ok(Type::Tuple(
+            elems
+                .into_iter()
+                .map(|elem| checker_type_of_ast_type(checker, elem, vars, new_vars))
+                .collect::<Result<_, _>>()?,
         )),
+        ast::types::Type::Record(ast::types::Record { fields, rest, .. }) => {
+            check_distinct_fields(fields.iter().map(|field| (&field.name, field.span)))?;
+            let rest = match rest {
+                Some(var) => {
+                    checker_type_of_ast_type(checker, ast::types::Type::Var(var), vars, new_vars)?
+                }
+                None => Type::RowEmpty,
+            };
+            let fields = fields
+                .into_iter()
+                .map(|field| {
+                    let typ = checker_type_of_ast_type(checker, field.typ, vars, new_vars)?;
+                    Ok((field.name, typ))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Type::Record(Box::new(row(fields, rest))))
+        }
+        ast::types::Type::Fun(ast::types::Fun { from, to, .. }) => Ok(Type::Fun(
+            Box::new(checker_type_of_ast_type(checker, *from, vars, new_vars)?),
+            Box::new(checker_type_of_ast_type(checker, *to, vars, new_vars)?),
+        )),
+    }
+}
+
+fn checker_type_of_type_app(
+    checker: &Checker,
+    name: String,
+    args: Vec<Type>,
+    span: Span,
+) -> Result<Type, Error> {
+    let (typ, arity) = match name.as_str() {
+        "Int" => (Type::Int, 0),
+        "Float" => (Type::Float, 0),
+        "Str" => (Type::Str, 0),
+        "Unit" => (Type::Unit, 0),
+        "Bool" => (Type::Bool, 0),
+        _ => match checker.resolve(&name, span, &checker.types)? {
+            qualified if checker.types.contains_key(&qualified) => {
+                let arity = checker.types[&qualified].arity;
+                (Type::Con(qualified, args.clone()), arity)
+            }
+            // A user-defined `List` shadows the built-in one
+            _ if name == "List" => match args.as_slice() {
+                [elem] => (Type::List(Box::new(elem.clone())), 1),
+                _ => (Type::Error, 1),
+            },
+            _ => return Err(Error::UnknownType { name, span }),
+        },
+    };
+    if args.len() == arity {
+        Ok(typ)
+    } else {
+        Err(Error::TypeArity {
+            name,
+            expected: arity,
+            found: args.len(),
+            span,
+        })
+    }
+}
+
+/// Checks every statement of the program, reporting all of the errors found rather than stopping
+/// at the first one
+pub fn check_program(program: Program) -> Result<CheckedProgram, Vec<Error>> {
+    check(program, false, &Globals::default())
+}
+
+/// Like [check_program], with `globals` in scope rather than just the prelude
+pub fn check_program_with(
+    program: Program,
+    globals: &Globals,
+) -> Result<CheckedProgram, Vec<Error>> {
+    check(program, false, globals)
+}
+
+/// Checks a program that is going to be run. On top of [check_program], this requires the program
+/// to define a `main` of type Unit or Int (its exit code).
+pub fn check_executable(program: Program) -> Result<CheckedProgram, Vec<Error>> {
+    check(program, true, &Globals::default())
+}
+
+/// Like [check_executable], with `globals` in scope rather than just the prelude
+pub fn check_executable_with(
+    program: Program,
+    globals: &Globals,
+) -> Result<CheckedProgram, Vec<Error>> {
+    check(program, true, globals)
+}
+
+/// Checks a program split across files. Each module is checked after the modules it imports,
+/// which it sees the top-level bindings of qualified by their name, e.g. `List.map`. The last
+/// module is the one being run, so it must define a `main` as for [check_executable]. Every
+/// module sees the `globals`.
+pub fn check_modules(
+    graph: ModuleGraph,
+    globals: &Globals,
+) -> Result<Vec<CheckedProgram>, Vec<ModuleError<Error>>> {
+    let last = graph.modules.len().saturating_sub(1);
+    // Mistakes in the globals are blamed on the module being run
+    let mut checker = checker_with(globals).map_err(|errs| {
+        errs.into_iter()
+            .map(|error| ModuleError {
+                module: ModuleId::Graph(last),
+                error,
+            })
+            .collect::<Vec<_>>()
+    })?;
+    let global_bindings = checker.bindings.clone();
+    let mut checked = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, module) in graph.modules.into_iter().enumerate() {
+        // Types and constructors are shared by every module, as their names are qualified by the
+        // module defining them, but each module has its own bindings
+        checker.bindings = global_bindings.clone();
+        checker.imports.clear();
+        checker.module = module.name.clone();
+        let defined = defined_names(&module.program);
+        match check_module(&mut checker, module.program, i == last) {
+            Ok(program) => checked.push(program),
+            Err(errs) => errors.extend(errs.into_iter().map(|error| ModuleError {
+                module: ModuleId::Graph(i),
+                error,
+            })),
+        }
+        if let Some(name) = module.name {
+            let exports = checker
+                .bindings
+                .iter()
+                .filter(|(name, _)| defined.contains(*name))
+                .map(|(name, poly)| (name.clone(), poly.clone()))
+                .collect();
+            checker.modules.insert(name, exports);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(checked)
+    } else {
+        Err(errors)
     }
 }
 
-pub fn check_prog_to_strings(program: Program) -> Result<Vec<(String, String)>, Error> {
+/// A statement that has been checked, but whose types haven't been concretized yet
+enum CheckedStmt {
+    Let {
+        name: String,
+        value: typed::Expr<Type>,
+        rec: bool,
+        poly: Polytype,
+        span: Span,
+    },
+    LetPattern {
+        pattern: typed::Pattern<Type>,
+        value: typed::Expr<Type>,
+        polys: Vec<(String, Polytype)>,
+        span: Span,
+    },
+    Declare {
+        name: String,
+        poly: Polytype,
+        span: Span,
+    },
+    TypeDef {
+        name: String,
+        constructors: Vec<(String, Polytype)>,
+        span: Span,
+    },
+    Import {
+        module: String,
+        span: Span,
+    },
+}
+
+fn check(
+    program: Program,
+    require_main: bool,
+    globals: &Globals,
+) -> Result<CheckedProgram, Vec<Error>> {
+    check_module(&mut checker_with(globals)?, program, require_main)
+}
+
+/// A checker with the definitions of the prelude in scope
+fn checker_with_prelude() -> Checker {
+    checker_with(&Globals::default()).expect("the prelude type checks")
+}
+
+/// A checker with the `globals` in scope. The builtins are checked as if they were `declare`d,
+/// after the prelude so that they can replace its declarations.
+fn checker_with(globals: &Globals) -> Result<Checker, Vec<Error>> {
     let mut checker = Checker::new();
+    if globals.prelude {
+        checker.module = Some(prelude::MODULE.to_string());
+        check_module(&mut checker, prelude::program(), false).expect("the prelude type checks");
+        checker.module = None;
+    }
+    let stmts = globals
+        .builtins
+        .iter()
+        .map(|(name, typ)| {
+            Stmt::Declare(stmt::Declare {
+                name: name.clone(),
+                typ: typ.clone(),
+                doc: None,
+                span: typ.span(),
+            })
+        })
+        .collect();
+    check_module(&mut checker, Program { stmts }, false)?;
+    Ok(checker)
+}
+
+/// The names of the top-level bindings a program defines itself, rather than getting from the
+/// prelude or an import
+fn defined_names(program: &Program) -> HashSet<String> {
+    program
+        .stmts
+        .iter()
+        .flat_map(|stmt| match stmt {
+            Stmt::Let(stmt::Let { name, .. }) | Stmt::Declare(stmt::Declare { name, .. }) => {
+                vec![name.clone()]
+            }
+            Stmt::LetPattern(stmt::LetPattern { pattern, .. }) => {
+                pattern.vars().into_iter().map(str::to_string).collect()
+            }
+            Stmt::TypeDef(_) | Stmt::Import(_) => Vec::new(),
+        })
+        .collect()
+}
+
+fn check_module(
+    checker: &mut Checker,
+    program: Program,
+    require_main: bool,
+) -> Result<CheckedProgram, Vec<Error>> {
+    let mut errors = Vec::new();
+    let defined = defined_names(&program);
+    // Types in earlier statements can still be refined by later ones, so statements are only
+    // concretized once the whole program has been checked
+    let mut stmts = Vec::new();
+    // Where the last definition of `main` is, if there is one
+    let mut main_span = None;
 
     for stmt in program.stmts {
-        match stmt {
-            Stmt::Let(stmt::Let { name, value, rec }) => {
-                checker.check_let_value(name, *value, rec)?;
+        let result = match stmt {
+            Stmt::Let(stmt::Let {
+                name,
+                value,
+                rec,
+                span,
+                ..
+            }) => {
+                if name == "main" {
+                    main_span = Some(value.span());
+                }
+                checker
+                    .check_top_level_let(name.clone(), *value, rec)
+                    .map(|value| {
+                        let poly = checker.bindings[&name].clone();
+                        CheckedStmt::Let {
+                            name,
+                            value,
+                            rec,
+                            poly,
+                            span,
+                        }
+                    })
             }
-            Stmt::Declare(stmt::Declare { name, typ }) => {
-                let typ = checker_type_of_ast_type(typ)?;
-                checker.insert_declare(name, typ);
+            Stmt::LetPattern(stmt::LetPattern {
+                pattern,
+                value,
+                span,
+                ..
+            }) => {
+                let names: Vec<_> = pattern.vars().into_iter().map(str::to_string).collect();
+                if names.iter().any(|name| name == "main") {
+                    main_span = Some(value.span());
+                }
+                checker
+                    .check_top_level_let_pattern(pattern, *value)
+                    .map(|(pattern, value)| {
+                        let polys = names
+                            .into_iter()
+                            .map(|name| {
+                                let poly = checker.bindings[&name].clone();
+                                (name, poly)
+                            })
+                            .collect();
+                        CheckedStmt::LetPattern {
+                            pattern,
+                            value,
+                            polys,
+                            span,
+                        }
+                    })
+            }
+            Stmt::Declare(stmt::Declare {
+                name, typ, span, ..
+            }) => {
+                if name == "main" {
+                    main_span = None;
+                }
+                // The type variables of a declaration are quantified, so they're made one level
+                // deeper for `insert_declare` to generalize them
+                checker.enter_level();
+                let typ = checker_type_of_ast_type(checker, typ, &mut HashMap::new(), true);
+                checker.exit_level();
+                match typ {
+                    Ok(typ) => {
+                        checker.insert_declare(name.clone(), typ);
+                        let poly = checker.bindings[&name].clone();
+                        Ok(CheckedStmt::Declare { name, poly, span })
+                    }
+                    Err(err) => {
+                        checker.insert_error(name);
+                        Err(err)
+                    }
+                }
+            }
+            Stmt::TypeDef(def) => {
+                let name = def.name.clone();
+                let span = def.span;
+                checker
+                    .define_type(def)
+                    .map(|constructors| CheckedStmt::TypeDef {
+                        name,
+                        constructors,
+                        span,
+                    })
+            }
+            Stmt::Import(stmt::Import { module, span }) => match checker.modules.get(&module) {
+                Some(bindings) => {
+                    for (name, poly) in bindings.clone() {
+                        checker
+                            .bindings
+                            .insert(format!("{}.{}", module, name), poly);
+                    }
+                    checker.imports.insert(module.clone());
+                    Ok(CheckedStmt::Import { module, span })
+                }
+                None => Err(Error::UnresolvedImport { module, span }),
+            },
+        };
+        match result {
+            Ok(stmt) => stmts.push(stmt),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    if require_main {
+        match main_span {
+            Some(span) => {
+                let main = checker.bindings["main"].clone();
+                match Concretizer::new(checker).concretize_polytype(main).ty {
+                    // A main that is polymorphic in its result never returns, so it's fine too
+                    Ty::Unit | Ty::Int | Ty::Var(_) | Ty::Error => {}
+                    found => errors.push(Error::MainType { found, span }),
+                }
             }
+            None => errors.push(Error::MissingMain),
         }
     }
 
-    // TODO: check type of main
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Bindings from the prelude or imports are only in scope, not defined here
+    let bindings = checker
+        .bindings
+        .iter()
+        .filter(|(name, _)| defined.contains(*name))
+        .map(|(name, poly)| {
+            let scheme = Concretizer::new(checker).concretize_polytype(poly.clone());
+            (name.clone(), scheme)
+        })
+        .collect();
 
-    let mut bindings: Vec<(String, String)> = checker
-        .get_bindings()
+    let stmts = stmts
         .into_iter()
-        .map(|(name, typ)| (name, string_of_type::string_of_type(&checker, typ)))
+        .map(|stmt| {
+            let mut concretizer = Concretizer::new(checker);
+            match stmt {
+                CheckedStmt::Let {
+                    name,
+                    value,
+                    rec,
+                    poly,
+                    span,
+                } => typed::Stmt::Let {
+                    name,
+                    scheme: concretizer.concretize_polytype(poly),
+                    value: value.map_types(&mut |t| concretizer.concretize(t)),
+                    rec,
+                    span,
+                },
+                CheckedStmt::LetPattern {
+                    pattern,
+                    value,
+                    polys,
+                    span,
+                } => typed::Stmt::LetPattern {
+                    pattern: pattern.map_types(&mut |t| concretizer.concretize(t)),
+                    value: value.map_types(&mut |t| concretizer.concretize(t)),
+                    schemes: polys
+                        .into_iter()
+                        .map(|(name, poly)| {
+                            let scheme = Concretizer::new(checker).concretize_polytype(poly);
+                            (name, scheme)
+                        })
+                        .collect(),
+                    span,
+                },
+                CheckedStmt::Declare { name, poly, span } => typed::Stmt::Declare {
+                    name,
+                    scheme: concretizer.concretize_polytype(poly),
+                    span,
+                },
+                CheckedStmt::TypeDef {
+                    name,
+                    constructors,
+                    span,
+                } => typed::Stmt::TypeDef {
+                    name,
+                    constructors: constructors
+                        .into_iter()
+                        .map(|(name, poly)| {
+                            let scheme = Concretizer::new(checker).concretize_polytype(poly);
+                            (name, scheme)
+                        })
+                        .collect(),
+                    span,
+                },
+                CheckedStmt::Import { module, span } => typed::Stmt::Import { module, span },
+            }
+        })
         .collect();
 
-    bindings.sort();
+    let mut warnings = std::mem::take(&mut checker.warnings);
+    warnings.sort_by_key(|warning| warning.span().start);
+
+    Ok(CheckedProgram {
+        bindings,
+        program: typed::Program { stmts },
+        warnings,
+    })
+}
 
-    Ok(bindings)
+pub fn check_prog_to_strings(program: Program) -> Result<Vec<(String, String)>, Vec<Error>> {
+    let checked = check_program(program)?;
+    Ok(checked
+        .bindings
+        .into_iter()
+        .map(|(name, scheme)| (name, scheme.to_string()))
+        .collect())
 }
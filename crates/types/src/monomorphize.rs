@@ -8,7 +8,7 @@ struct Monomorphize<'a> {
 }
 
 impl<'a> Monomorphize<'a> {
-    fn new(checker: &mut Checker) -> Monomorphize {
+    fn new(checker: &mut Checker) -> Monomorphize<'_> {
         Monomorphize {
             checker,
             to_replace: HashMap::new(),
@@ -26,9 +26,12 @@ impl<'a> Monomorphize<'a> {
     fn replace(&mut self, typ: Type) -> Type {
         match typ {
             Type::Int => Type::Int,
+            Type::Float => Type::Float,
             Type::Str => Type::Str,
             Type::Unit => Type::Unit,
             Type::Bool => Type::Bool,
+            Type::Error => Type::Error,
+            Type::RowEmpty => Type::RowEmpty,
             Type::Var(tvar) => match self.checker.tvars.get(tvar) {
                 TVar::Bound(t) => self.replace(t.clone()),
                 TVar::Unbound(id, _) => match self.to_replace.get(id) {
@@ -37,6 +40,20 @@ impl<'a> Monomorphize<'a> {
                 },
             },
             Type::Fun(a, b) => Type::Fun(Box::new(self.replace(*a)), Box::new(self.replace(*b))),
+            Type::Con(name, args) => Type::Con(
+                name,
+                args.into_iter().map(|arg| self.replace(arg)).collect(),
+            ),
+            Type::Tuple(elems) => {
+                Type::Tuple(elems.into_iter().map(|elem| self.replace(elem)).collect())
+            }
+            Type::List(elem) => Type::List(Box::new(self.replace(*elem))),
+            Type::Record(row) => Type::Record(Box::new(self.replace(*row))),
+            Type::RowExtend(name, field, rest) => Type::RowExtend(
+                name,
+                Box::new(self.replace(*field)),
+                Box::new(self.replace(*rest)),
+            ),
         }
     }
 }
@@ -8,7 +8,7 @@ struct Polymorphize<'a> {
 }
 
 impl<'a> Polymorphize<'a> {
-    fn new(checker: &'a mut Checker) -> Polymorphize {
+    fn new(checker: &'a mut Checker) -> Polymorphize<'a> {
         Polymorphize {
             checker,
             vars: HashSet::new(),
@@ -23,7 +23,13 @@ impl<'a> Polymorphize<'a> {
 
     fn collect_vars(&mut self, typ: Type) {
         match typ {
-            Type::Int | Type::Str | Type::Unit | Type::Bool => (),
+            Type::Int
+            | Type::Float
+            | Type::Str
+            | Type::Unit
+            | Type::Bool
+            | Type::RowEmpty
+            | Type::Error => (),
             Type::Var(tvar) => match self.checker.tvars.get(tvar) {
                 TVar::Bound(t) => self.collect_vars(t.clone()),
                 TVar::Unbound(id, level) => {
@@ -36,6 +42,16 @@ impl<'a> Polymorphize<'a> {
                 self.collect_vars(*a);
                 self.collect_vars(*b);
             }
+            Type::Con(_, elems) | Type::Tuple(elems) => {
+                for elem in elems {
+                    self.collect_vars(elem);
+                }
+            }
+            Type::List(elem) | Type::Record(elem) => self.collect_vars(*elem),
+            Type::RowExtend(_, field, rest) => {
+                self.collect_vars(*field);
+                self.collect_vars(*rest);
+            }
         }
     }
 }
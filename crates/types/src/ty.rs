@@ -0,0 +1,79 @@
+/// A type as reported by the type checker. Unlike the checker's internal representation, every
+/// type variable has already been resolved as far as it can be, and the ones that remain are named.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub enum Ty {
+    Int,
+    Float,
+    Str,
+    Unit,
+    Bool,
+    /// A type variable, e.g. `a` for `'a`
+    Var(String),
+    Fun(Box<Ty>, Box<Ty>),
+    /// A user-defined type applied to its arguments, e.g. `Option Int`
+    Con(String, Vec<Ty>),
+    /// e.g. `Int * Str`
+    Tuple(Vec<Ty>),
+    /// e.g. `List Int`
+    List(Box<Ty>),
+    /// A record's fields, sorted by name, and the type variable standing for any other fields it
+    /// may have, e.g. `{ name : Str | 'a }`
+    Record(Vec<(String, Ty)>, Option<String>),
+    /// The type of something that failed to type check
+    Error,
+}
+
+/// A possibly polymorphic type, e.g. `forall 'a. 'a -> 'a`
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub struct Scheme {
+    /// The quantified type variables, in order of their first appearance in `ty`
+    pub vars: Vec<String>,
+    pub ty: Ty,
+}
+
+impl std::fmt::Display for Ty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ty::Int => write!(f, "Int"),
+            Ty::Float => write!(f, "Float"),
+            Ty::Str => write!(f, "Str"),
+            Ty::Unit => write!(f, "Unit"),
+            Ty::Bool => write!(f, "Bool"),
+            Ty::Var(name) => write!(f, "'{}", name),
+            Ty::Fun(a, b) => write!(f, "({} -> {})", a, b),
+            Ty::Con(name, args) if args.is_empty() => write!(f, "{}", name),
+            Ty::Con(name, args) => {
+                write!(f, "({}", name)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Ty::Tuple(elems) => {
+                let elems: Vec<_> = elems.iter().map(|elem| elem.to_string()).collect();
+                write!(f, "({})", elems.join(" * "))
+            }
+            Ty::List(elem) => write!(f, "(List {})", elem),
+            Ty::Record(fields, rest) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(name, ty)| format!("{} : {}", name, ty))
+                    .collect();
+                write!(f, "{{ {}", fields.join("; "))?;
+                match rest {
+                    Some(rest) if fields.is_empty() => write!(f, "| '{} }}", rest),
+                    Some(rest) => write!(f, " | '{} }}", rest),
+                    None => write!(f, " }}"),
+                }
+            }
+            Ty::Error => write!(f, "<error>"),
+        }
+    }
+}
+
+// Quantifiers are left implicit, as they would be written in source
+impl std::fmt::Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.ty)
+    }
+}
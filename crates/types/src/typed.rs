@@ -0,0 +1,275 @@
+//! The AST annotated with the type of every expression, as inferred by the checker
+
+use pandalang_parser::{ast::expr::BinOpKind, span::Span};
+
+use crate::{Scheme, Ty};
+
+// The checker builds this tree with its internal types and concretizes it once every type is
+// known, so nodes are generic over the type. Consumers only ever see `Ty`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Expr<T = Ty> {
+    pub kind: ExprKind<T>,
+    pub ty: T,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum ExprKind<T = Ty> {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Unit,
+    Bool(bool),
+    Var(String),
+    Constructor(String),
+    Tuple(Vec<Expr<T>>),
+    List(Vec<Expr<T>>),
+    Cons {
+        head: Box<Expr<T>>,
+        tail: Box<Expr<T>>,
+    },
+    Record(Vec<(String, Expr<T>)>),
+    Access {
+        expr: Box<Expr<T>>,
+        field: String,
+    },
+    Update {
+        expr: Box<Expr<T>>,
+        fields: Vec<(String, Expr<T>)>,
+    },
+    BinOp {
+        left: Box<Expr<T>>,
+        right: Box<Expr<T>>,
+        kind: BinOpKind,
+    },
+    Not {
+        expr: Box<Expr<T>>,
+    },
+    Let {
+        name: String,
+        value: Box<Expr<T>>,
+        body: Box<Expr<T>>,
+        rec: bool,
+    },
+    Fun {
+        arg: String,
+        body: Box<Expr<T>>,
+    },
+    App {
+        fun: Box<Expr<T>>,
+        arg: Box<Expr<T>>,
+    },
+    If {
+        check: Box<Expr<T>>,
+        then: Box<Expr<T>>,
+        els: Box<Expr<T>>,
+    },
+    Match {
+        expr: Box<Expr<T>>,
+        arms: Vec<Arm<T>>,
+    },
+    Ascribe {
+        expr: Box<Expr<T>>,
+    },
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Arm<T = Ty> {
+    pub pattern: Pattern<T>,
+    pub body: Expr<T>,
+    pub span: Span,
+}
+
+/// A pattern annotated with the type of the values it matches
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Pattern<T = Ty> {
+    pub kind: PatternKind<T>,
+    pub ty: T,
+    pub span: Span,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum PatternKind<T = Ty> {
+    Wildcard,
+    Var(String),
+    Int(i64),
+    Str(String),
+    Unit,
+    Bool(bool),
+    Constructor {
+        name: String,
+        args: Vec<Pattern<T>>,
+    },
+    Tuple(Vec<Pattern<T>>),
+    List(Vec<Pattern<T>>),
+    Cons {
+        head: Box<Pattern<T>>,
+        tail: Box<Pattern<T>>,
+    },
+    Record(Vec<(String, Pattern<T>)>),
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Stmt {
+    Let {
+        name: String,
+        value: Expr,
+        rec: bool,
+        scheme: Scheme,
+        span: Span,
+    },
+    /// A `let` that destructures its value, with the type of each variable the pattern binds
+    LetPattern {
+        pattern: Pattern,
+        value: Expr,
+        schemes: Vec<(String, Scheme)>,
+        span: Span,
+    },
+    Declare {
+        name: String,
+        scheme: Scheme,
+        span: Span,
+    },
+    TypeDef {
+        name: String,
+        /// The type of each constructor
+        constructors: Vec<(String, Scheme)>,
+        span: Span,
+    },
+    Import {
+        module: String,
+        span: Span,
+    },
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Program {
+    pub stmts: Vec<Stmt>,
+}
+
+impl<T> Expr<T> {
+    pub(crate) fn map_types<U>(self, f: &mut impl FnMut(T) -> U) -> Expr<U> {
+        let mut map = |e: Box<Expr<T>>| Box::new(e.map_types(f));
+        let kind = match self.kind {
+            ExprKind::Int(n) => ExprKind::Int(n),
+            ExprKind::Float(x) => ExprKind::Float(x),
+            ExprKind::Str(s) => ExprKind::Str(s),
+            ExprKind::Unit => ExprKind::Unit,
+            ExprKind::Bool(b) => ExprKind::Bool(b),
+            ExprKind::Var(name) => ExprKind::Var(name),
+            ExprKind::Constructor(name) => ExprKind::Constructor(name),
+            ExprKind::Tuple(elems) => {
+                ExprKind::Tuple(elems.into_iter().map(|elem| elem.map_types(f)).collect())
+            }
+            ExprKind::List(elems) => {
+                ExprKind::List(elems.into_iter().map(|elem| elem.map_types(f)).collect())
+            }
+            ExprKind::Cons { head, tail } => ExprKind::Cons {
+                head: map(head),
+                tail: map(tail),
+            },
+            ExprKind::Record(fields) => ExprKind::Record(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name, value.map_types(f)))
+                    .collect(),
+            ),
+            ExprKind::Access { expr, field } => ExprKind::Access {
+                expr: map(expr),
+                field,
+            },
+            ExprKind::Update { expr, fields } => ExprKind::Update {
+                expr: map(expr),
+                fields: fields
+                    .into_iter()
+                    .map(|(name, value)| (name, value.map_types(f)))
+                    .collect(),
+            },
+            ExprKind::BinOp { left, right, kind } => ExprKind::BinOp {
+                left: map(left),
+                right: map(right),
+                kind,
+            },
+            ExprKind::Not { expr } => ExprKind::Not { expr: map(expr) },
+            ExprKind::Let {
+                name,
+                value,
+                body,
+                rec,
+            } => ExprKind::Let {
+                name,
+                value: map(value),
+                body: map(body),
+                rec,
+            },
+            ExprKind::Fun { arg, body } => ExprKind::Fun {
+                arg,
+                body: map(body),
+            },
+            ExprKind::App { fun, arg } => ExprKind::App {
+                fun: map(fun),
+                arg: map(arg),
+            },
+            ExprKind::If { check, then, els } => ExprKind::If {
+                check: map(check),
+                then: map(then),
+                els: map(els),
+            },
+            ExprKind::Match { expr, arms } => ExprKind::Match {
+                expr: map(expr),
+                arms: arms
+                    .into_iter()
+                    .map(|arm| Arm {
+                        pattern: arm.pattern.map_types(f),
+                        body: arm.body.map_types(f),
+                        span: arm.span,
+                    })
+                    .collect(),
+            },
+            ExprKind::Ascribe { expr } => ExprKind::Ascribe { expr: map(expr) },
+        };
+        Expr {
+            kind,
+            ty: f(self.ty),
+            span: self.span,
+        }
+    }
+}
+
+impl<T> Pattern<T> {
+    pub(crate) fn map_types<U>(self, f: &mut impl FnMut(T) -> U) -> Pattern<U> {
+        let kind = match self.kind {
+            PatternKind::Wildcard => PatternKind::Wildcard,
+            PatternKind::Var(name) => PatternKind::Var(name),
+            PatternKind::Int(n) => PatternKind::Int(n),
+            PatternKind::Str(s) => PatternKind::Str(s),
+            PatternKind::Unit => PatternKind::Unit,
+            PatternKind::Bool(b) => PatternKind::Bool(b),
+            PatternKind::Constructor { name, args } => PatternKind::Constructor {
+                name,
+                args: args.into_iter().map(|arg| arg.map_types(f)).collect(),
+            },
+            PatternKind::Tuple(elems) => {
+                PatternKind::Tuple(elems.into_iter().map(|elem| elem.map_types(f)).collect())
+            }
+            PatternKind::List(elems) => {
+                PatternKind::List(elems.into_iter().map(|elem| elem.map_types(f)).collect())
+            }
+            PatternKind::Cons { head, tail } => PatternKind::Cons {
+                head: Box::new(head.map_types(f)),
+                tail: Box::new(tail.map_types(f)),
+            },
+            PatternKind::Record(fields) => PatternKind::Record(
+                fields
+                    .into_iter()
+                    .map(|(name, pattern)| (name, pattern.map_types(f)))
+                    .collect(),
+            ),
+        };
+        Pattern {
+            kind,
+            ty: f(self.ty),
+            span: self.span,
+        }
+    }
+}